@@ -7,6 +7,12 @@ pub struct CreatorArgs {
     pub ark_path: String,
     #[arg(short, long, help = "Default outfit to load (i.e. alterna1, grim)")]
     pub default_outfit: Option<String>,
+    #[arg(short, long, help = "Patch ark to load on top of input ark (can be repeated)")]
+    pub patch_ark: Vec<String>,
+    #[arg(short, long, help = "Directory of loose files to load on top of arks (can be repeated)")]
+    pub loose_dir: Vec<String>,
+    #[arg(long, help = "Venue to load (i.e. arena, battle, fest)")]
+    pub venue: Option<String>,
 }
 
 impl CreatorArgs {
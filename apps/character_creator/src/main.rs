@@ -14,9 +14,15 @@ use std::collections::HashMap;
 const _PROJECT_NAME: &str = env!("CARGO_PKG_NAME");
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const DEFAULT_CHARACTER: &str = "char/alterna1/og/alterna1_ui.milo";
+//const DEFAULT_CHARACTER: &str = "char/grim/og/grim_ui.milo";
+
 #[derive(Component)]
 pub struct SelectedCharacter;
 
+#[derive(Default, Resource)]
+pub struct SelectedVenue(pub Option<String>);
+
 /*#[derive(Component)]
 pub enum HelpText {
     Character(String),
@@ -56,34 +62,54 @@ fn main() {
         //.insert_resource(Msaa::Sample4)
         .add_plugins(MiloPlugin {
             ark_path: Some(args.ark_path.into()),
+            sources: args.patch_ark
+                .into_iter()
+                .map(|p| MiloSourcePath::Ark(p.into()))
+                .chain(args.loose_dir
+                    .into_iter()
+                    .map(|d| MiloSourcePath::Directory(d.into())))
+                .collect(),
             default_outfit: args.default_outfit,
             ..Default::default()
         })
+        .add_plugins(MiloPickingPlugin)
         .insert_resource(CharacterAnimations::default())
+        .insert_resource(SelectedVenue(args.venue))
         .add_plugins(FlyCameraPlugin)
         .add_plugins(InfiniteGridPlugin)
         .add_systems(Startup, init_milos)
         .add_systems(Startup, setup)
-        .add_systems(Update, fix_meta_proxy_cam)
         .add_systems(Update, control_camera)
         //.add_systems(Update, active_camera_change)
         //.add_system(attach_free_cam)
         .add_systems(Update, load_default_character)
         .add_systems(PostUpdate, set_placer_as_char_parent)
         .add_systems(Update, print_trans_hierarchy)
+        .add_systems(Update, print_selected_object)
         .run();
 }
 
 // TODO: Move to separate file?
 fn init_milos(
     mut scene_events_writer: EventWriter<LoadMiloScene>,
+    mut venue_events_writer: EventWriter<LoadVenue>,
+    mut band: ResMut<MiloVenueBand>,
+    venue: Res<SelectedVenue>,
 ) {
+    // Venue replaces character select scene
+    if let SelectedVenue(Some(venue)) = venue.as_ref() {
+        band.members.push(MiloBandMember {
+            placer: String::from("guitar"),
+            milo_path: DEFAULT_CHARACTER.to_owned(),
+        });
+
+        venue_events_writer.write(LoadVenue(venue.to_owned()));
+        return;
+    }
+
     let default_files = [
         "ui/sel_character.milo",
         "ui/metacam.milo",
-        //"world/battle/og/battle_geom.milo",
-        //"world/arena/og/arena_geom.milo",
-        //"world/fest/og/fest_geom.milo"
     ];
 
     for milo_path in default_files {
@@ -164,35 +190,29 @@ fn setup(
 fn control_camera(
     key_input: Res<ButtonInput<KeyCode>>,
     mouse_input: Res<ButtonInput<MouseButton>>,
-    mut cam_query: Query<(&mut Camera, Option<&mut FlyCamera>, Option<&Name>)>,
+    mut set_cam_events_writer: EventWriter<SetActiveMiloCam>,
+    mut cam_query: Query<(&Camera, Option<&mut FlyCamera>, &Name)>,
 ) {
     let key_down = is_camera_button_down(&key_input);
     let mouse_down = mouse_input.pressed(MouseButton::Left);
 
-    let count = cam_query.iter().count();
-    let cycle_cam = key_input.any_just_released([KeyCode::KeyC]);
-
-    let current_idx = cam_query
-        .iter()
-        .enumerate()
-        .find(|(_, (c, ..))| c.is_active)
-        .map(|(i, _)| i)
-        .unwrap_or_default();
-
-    let next_idx = match (cycle_cam, current_idx + 1) {
-        (true, next) if next < count => next,
-        (true, _) => 0,
-        _ => current_idx
-    };
+    if key_input.any_just_released([KeyCode::KeyC]) {
+        let cam_names = cam_query
+            .iter()
+            .map(|(_, _, n)| n.as_str())
+            .collect::<Vec<_>>();
 
-    for (i, (mut cam, fly_cam, name)) in cam_query.iter_mut().enumerate() {
-        let was_active = cam.is_active;
-        cam.is_active = i == next_idx;
+        let current_idx = cam_query
+            .iter()
+            .position(|(c, ..)| c.is_active)
+            .unwrap_or_default();
 
-        if was_active != cam.is_active {
-            log::debug!("Cam {} is now active", name.as_ref().map(|n| n.as_str()).unwrap_or("(unknown)"));
+        if let Some(next_cam) = cam_names.get((current_idx + 1) % cam_names.len().max(1)) {
+            set_cam_events_writer.write(SetActiveMiloCam(next_cam.to_string()));
         }
+    }
 
+    for (_, fly_cam, _) in cam_query.iter_mut() {
         if let Some(mut fly_cam) = fly_cam {
             // Disable camera move if mouse button not held
             fly_cam.sensitivity = match mouse_down {
@@ -230,19 +250,6 @@ fn is_camera_button_down(key_input: &Res<ButtonInput<KeyCode>>) -> bool {
         .any(|k| key_input.pressed(*k))
 }
 
-fn fix_meta_proxy_cam(
-    mut cam_query: Query<(&mut Transform, &Name), (Added<Transform>, With<Camera>)>,
-) {
-    const CAM_NAME: &str = "meta.cam";
-
-    for (mut trans, name) in cam_query.iter_mut() {
-        if name.as_str() == CAM_NAME {
-            *trans = trans.looking_at(Vec3::ZERO, Vec3::Z);
-            log::error!("Patched transform in {CAM_NAME}");
-        }
-    }
-}
-
 fn print_trans_hierarchy(
     key_input: Res<ButtonInput<KeyCode>>,
     root_query: Query<Entity, With<MiloRoot>>,
@@ -267,6 +274,18 @@ fn print_trans_hierarchy(
     print_children(root_entity, &trans_map, 0);
 }
 
+fn print_selected_object(
+    mut selected_events_reader: EventReader<MiloEntitySelected>,
+    milo_query: Query<&MiloObject>,
+) {
+    for MiloEntitySelected(entity) in selected_events_reader.read() {
+        match entity.and_then(|en| milo_query.get(en).ok()) {
+            Some(obj) => println!("Selected {} ({})", &obj.name, &obj.dir),
+            _ => println!("Cleared selection"),
+        }
+    }
+}
+
 fn print_children(
     parent_entity: Entity,
     trans_map: &HashMap<Entity, (Option<&Name>, Option<&Children>)>,
@@ -314,8 +333,14 @@ fn load_default_character(
     mut animations: ResMut<Assets<AnimationClip>>,
     _animation_graphs: ResMut<Assets<AnimationGraph>>,
     placer_query: Query<(Entity, &Name), Added<MiloBandPlacer>>,
+    venue: Res<SelectedVenue>,
     _state: Res<MiloState>,
 ) {
+    // Venue places character itself
+    if venue.0.is_some() {
+        return;
+    }
+
     let Ok((placer_entity, placer_name)) = placer_query.single() else {
         return
     };
@@ -323,8 +348,7 @@ fn load_default_character(
     // Load character
     scene_events_writer.write(
         LoadMiloSceneWithCommands(
-            "char/alterna1/og/alterna1_ui.milo".into(),
-            //"char/grim/og/grim_ui.milo".into(),
+            DEFAULT_CHARACTER.into(),
             |commands| {
                 commands.insert(SelectedCharacter);
             }
@@ -442,17 +466,19 @@ fn set_placer_as_char_parent(
     for (entity, _parent, obj) in char_objects_query.iter() {
         let obj_dir_name = obj.dir.as_str();
 
-        let Some(obj) = state.objects.get(obj.id as usize) else {
+        let Some(obj) = state.get_object(obj.id) else {
             continue;
         };
 
         let trans_parent = match obj {
             Object::BandPlacer(obj) => &obj.parent,
             Object::Cam(obj) => &obj.parent,
+            Object::CharCollide(obj) => &obj.parent,
             Object::Mesh(obj) => &obj.parent,
             Object::Group(obj) => &obj.parent,
+            Object::Text(obj) => &obj.parent,
             Object::Trans(obj) => &obj.parent,
-            _ => todo!("Shouldn't happen")
+            _ => continue // Not a trans (i.e. hair)
         };
 
         // Add root-level trans objects to placer
@@ -1,8 +1,12 @@
 use bevy::prelude::*;
+use crate::render::GltfExportSettings;
+use pikaxe::SystemInfo;
+use pikaxe::io::BlockType;
 use std::path::PathBuf;
 
 #[derive(Event)]
 pub enum AppEvent {
+    Close, // Closes open ark + milo
     Exit,
     SelectMiloEntry(Option<String>),
     ToggleGridLines(bool),
@@ -12,4 +16,13 @@ pub enum AppEvent {
 #[derive(Event)]
 pub enum AppFileEvent {
     Open(PathBuf),
+    OpenArkEntry(String), // Path of entry in open ark
+    ExtractArkEntries(Vec<String>, PathBuf), // Entry paths + output dir
+    Save,
+    SaveAs(PathBuf, SystemInfo, BlockType),
+    VerifyRoundTrip, // Dry run, compares re-saved milo with original
+    ExportTexture(String, PathBuf), // Tex name + png path
+    ImportTexture(String, PathBuf), // Tex name + png path
+    ExportGltf(PathBuf, GltfExportSettings),
+    ImportModel(PathBuf), // Gltf/glb/obj
 }
\ No newline at end of file
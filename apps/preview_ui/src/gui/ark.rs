@@ -1,40 +1,197 @@
 use bevy_egui::{EguiContext, EguiPlugin, egui, egui::{Color32, Context, Pos2, Ui}};
 use pikaxe::ark::{Ark, ArkOffsetEntry};
-use super::{AppSettings, AppState, ArkDirNode, AppEvent};
+use super::{AppSettings, AppState, ArkAction, ArkDirNode, AppEvent, AppFileEvent, FileType, get_file_type};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+const STAGED_COLOR: Color32 = Color32::from_rgb(255, 200, 64);
+
+struct ArkTreeView<'a> {
+    entries: &'a Vec<ArkOffsetEntry>,
+    open_entry: Option<&'a str>,
+    staged_files: &'a BTreeMap<String, PathBuf>,
+}
 
 pub fn draw_ark_tree(state: &mut AppState, ctx: &mut &Context, ui: &mut Ui) {
+    let mut entry_to_open = None;
+    let mut action = None;
+
     if let Some(root) = &state.root {
-        let entries = &state.ark.as_ref().unwrap().entries;
+        let view = ArkTreeView {
+            entries: &state.ark.as_ref().unwrap().entries,
+            open_entry: state.open_ark_entry.as_deref(),
+            staged_files: &state.ark_view.staged_files,
+        };
+
+        draw_node(root, &view, &mut entry_to_open, &mut action, ctx, ui);
+    }
 
-        draw_node(root, entries, ctx, ui);
+    if let Some(entry_path) = entry_to_open {
+        state.add_file_event(AppFileEvent::OpenArkEntry(entry_path));
+    }
+
+    if let Some(action) = action {
+        state.ark_view.action = Some(action);
     }
 }
 
-fn draw_node(node: &ArkDirNode, entries: &Vec<ArkOffsetEntry>, ctx: &mut &Context, ui: &mut Ui) {
-    egui::CollapsingHeader::new(&node.name)
+fn draw_node(node: &ArkDirNode, view: &ArkTreeView, entry_to_open: &mut Option<String>, action: &mut Option<ArkAction>, ctx: &mut &Context, ui: &mut Ui) {
+    let header = egui::CollapsingHeader::new(&node.name)
         .id_salt(format!("dir_{}", &node.path))
         .default_open(false)
         .show(ui, |ui| {
             for child in &node.dirs {
-                draw_node(child, entries, ctx, ui);
+                draw_node(child, view, entry_to_open, action, ctx, ui);
             }
 
             egui::Grid::new(format!("files_{}", &node.path)).striped(true).show(ui, |ui| {
                 for file_idx in &node.files {
-                    let ark_entry = &entries[*file_idx];
+                    let ark_entry = &view.entries[*file_idx];
                     let file_name = get_file_name(&ark_entry.path);
 
-                    #[allow(unused_must_use)] {
-                        ui.selectable_label(false, file_name);
+                    let is_open = view.open_entry.is_some_and(|p| p.eq(&ark_entry.path));
+                    let mut response = match view.staged_files.get(&ark_entry.path) {
+                        Some(local_path) => ui
+                            .selectable_label(is_open, egui::RichText::new(format!("{file_name} *")).color(STAGED_COLOR))
+                            .on_hover_text(format!("Replaced with \"{}\"", local_path.display())),
+                        _ => ui.selectable_label(is_open, file_name)
+                    };
+
+                    if get_file_type(file_name) == Some(FileType::Milo) {
+                        response = response.on_hover_text("Double-click to open");
+
+                        if response.double_clicked() {
+                            *entry_to_open = Some(ark_entry.path.to_owned());
+                        }
                     }
+
+                    response.context_menu(|ui| {
+                        if ui.button("Extract...").clicked() {
+                            *action = Some(ArkAction::Extract(vec![ark_entry.path.to_owned()]));
+                            ui.close_menu();
+                        }
+
+                        if ui.button("Replace...").clicked() {
+                            *action = Some(ArkAction::Replace(ark_entry.path.to_owned()));
+                            ui.close_menu();
+                        }
+                    });
+
                     ui.end_row();
 
                     //ui.small_button(file_name);
                 }
+
+                // Files staged to be added on save
+                for (ark_path, local_path) in view.staged_files.iter() {
+                    if get_dir_path(ark_path).ne(&node.path) || view.entries.iter().any(|e| e.path.eq(ark_path)) {
+                        continue;
+                    }
+
+                    ui.label(egui::RichText::new(format!("{} (new)", get_file_name(ark_path))).color(STAGED_COLOR))
+                        .on_hover_text(format!("Added from \"{}\"", local_path.display()));
+                    ui.end_row();
+                }
+            });
+        });
+
+    header.header_response.context_menu(|ui| {
+        if ui.button("Extract...").clicked() {
+            let entry_paths = view.entries
+                .iter()
+                .filter(|e| node.path.is_empty() || e.path.starts_with(&format!("{}/", &node.path)))
+                .map(|e| e.path.to_owned())
+                .collect();
+
+            *action = Some(ArkAction::Extract(entry_paths));
+            ui.close_menu();
+        }
+
+        if ui.button("Add...").clicked() {
+            *action = Some(ArkAction::Add(node.path.to_owned()));
+            ui.close_menu();
+        }
+    });
+}
+
+pub fn draw_ark_action_window(state: &mut AppState, ctx: &mut &Context) {
+    let Some(action) = &state.ark_view.action else {
+        return;
+    };
+
+    let (title, hint, confirm_text) = match action {
+        ArkAction::Extract(entry_paths) => (format!("Extract {} file(s)", entry_paths.len()), "Output folder", "Extract"),
+        ArkAction::Replace(entry_path) => (format!("Replace \"{}\"", entry_path), "Local file", "Replace"),
+        ArkAction::Add(dir_path) => (format!("Add to \"{}\"", dir_path), "Local file", "Add"),
+    };
+
+    let mut confirmed = false;
+    let mut cancelled = false;
+
+    egui::Window::new(title)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(hint);
+                ui.add(egui::TextEdit::singleline(&mut state.ark_view.action_path).desired_width(400.0));
+            });
+
+            ui.horizontal(|ui| {
+                confirmed = ui.button(confirm_text).clicked();
+                cancelled = ui.button("Cancel").clicked();
             });
         });
+
+    if cancelled {
+        state.ark_view.action = None;
+        return;
+    }
+
+    if !confirmed {
+        return;
+    }
+
+    let path = PathBuf::from(state.ark_view.action_path.trim());
+
+    match state.ark_view.action.take().unwrap() {
+        ArkAction::Extract(entry_paths) => {
+            state.add_file_event(AppFileEvent::ExtractArkEntries(entry_paths, path));
+        },
+        ArkAction::Replace(entry_path) => {
+            stage_ark_file(state, entry_path, path);
+        },
+        ArkAction::Add(dir_path) => {
+            let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+                log::warn!("Invalid file path \"{}\"", path.display());
+                return;
+            };
+
+            let ark_path = match dir_path.as_str() {
+                "" => file_name.to_owned(),
+                _ => format!("{dir_path}/{file_name}")
+            };
+
+            stage_ark_file(state, ark_path, path);
+        },
+    }
+}
+
+fn stage_ark_file(state: &mut AppState, ark_path: String, local_path: PathBuf) {
+    if !local_path.is_file() {
+        log::warn!("Can't find file \"{}\"", local_path.display());
+        return;
+    }
+
+    log::info!("Staged \"{}\" as \"{}\"", local_path.display(), &ark_path);
+    state.ark_view.staged_files.insert(ark_path, local_path);
 }
 
 pub fn get_file_name(path: &str) -> &str {
     path.split('/').last().unwrap_or(path)
-}
\ No newline at end of file
+}
+
+pub fn get_dir_path(path: &str) -> &str {
+    path.rsplit_once('/').map(|(d, _)| d).unwrap_or_default()
+}
@@ -0,0 +1,162 @@
+use bevy_egui::{EguiContext, EguiPlugin, egui, egui::{Color32, Context, Pos2, Ui}};
+use super::{AppSettings, AppState, AppFileEvent, FileBrowserView, get_file_type};
+use std::path::{Path, PathBuf};
+
+impl FileBrowserView {
+    pub fn show<T: AsRef<Path>>(&mut self, dir_path: Option<T>) {
+        let dir_path = dir_path
+            .map(|p| p.as_ref().to_owned())
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_default();
+
+        self.open = true;
+        self.set_dir(dir_path);
+    }
+
+    pub fn set_dir(&mut self, dir_path: PathBuf) {
+        let Ok(read_dir) = std::fs::read_dir(&dir_path) else {
+            log::warn!("Unable to read directory \"{}\"", dir_path.display());
+            return;
+        };
+
+        let (mut dirs, mut files): (Vec<_>, Vec<_>) = read_dir
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .partition(|p| p.is_dir());
+
+        dirs.sort_by_key(|p| get_name(p).to_ascii_lowercase());
+        files.sort_by_key(|p| get_name(p).to_ascii_lowercase());
+
+        self.path_text = dir_path.to_string_lossy().to_string();
+        self.current_dir = dir_path;
+        self.selected_file = None;
+        self.dirs = dirs;
+        self.files = files;
+    }
+}
+
+pub fn draw_file_browser(ctx: &mut &Context, settings: &mut AppSettings, state: &mut AppState) {
+    if !state.file_browser.open {
+        return;
+    }
+
+    let mut open = true;
+    let mut file_to_open = None;
+    let mut dir_to_open = None;
+
+    let browser = &mut state.file_browser;
+
+    egui::Window::new("Open")
+        .collapsible(false)
+        .default_size([640.0, 480.0])
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .open(&mut open)
+        .show(ctx, |ui| {
+            // Path bar
+            ui.horizontal(|ui| {
+                let can_go_up = browser.current_dir.parent().is_some();
+
+                if ui.add_enabled(can_go_up, egui::Button::new("Up")).clicked() {
+                    dir_to_open = browser.current_dir.parent().map(|p| p.to_owned());
+                }
+
+                let response = ui.add(egui::TextEdit::singleline(&mut browser.path_text).desired_width(f32::INFINITY));
+
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    let path = PathBuf::from(browser.path_text.trim());
+
+                    if path.is_file() {
+                        file_to_open = Some(path);
+                    } else {
+                        dir_to_open = Some(path);
+                    }
+                }
+            });
+
+            ui.separator();
+
+            egui::SidePanel::left("file_browser_recent")
+                .resizable(false)
+                .default_width(160.0)
+                .show_inside(ui, |ui| {
+                    ui.heading("Recent");
+
+                    if settings.recent_dirs.is_empty() {
+                        ui.label("No recent locations");
+                    }
+
+                    for dir in settings.recent_dirs.iter() {
+                        let name = get_name(Path::new(dir));
+
+                        if ui.selectable_label(browser.current_dir.eq(Path::new(dir)), name)
+                            .on_hover_text(dir)
+                            .clicked() {
+                            dir_to_open = Some(PathBuf::from(dir));
+                        }
+                    }
+                });
+
+            egui::TopBottomPanel::bottom("file_browser_actions")
+                .show_inside(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut browser.show_all_files, "Show all files");
+
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            let can_open = browser.selected_file.is_some();
+
+                            if ui.add_enabled(can_open, egui::Button::new("Open")).clicked() {
+                                file_to_open = browser.selected_file.to_owned();
+                            }
+                        });
+                    });
+                });
+
+            // Directory listing
+            egui::ScrollArea::vertical().auto_shrink(false).show(ui, |ui| {
+                for dir in browser.dirs.iter() {
+                    if ui.selectable_label(false, format!("{}/", get_name(dir))).double_clicked() {
+                        dir_to_open = Some(dir.to_owned());
+                    }
+                }
+
+                for file in browser.files.iter().filter(|f| browser.show_all_files || is_known_file(f)) {
+                    let selected = browser.selected_file.as_ref().is_some_and(|s| s.eq(file));
+                    let response = ui.selectable_label(selected, get_name(file));
+
+                    if response.clicked() {
+                        browser.selected_file = Some(file.to_owned());
+                    }
+
+                    if response.double_clicked() {
+                        file_to_open = Some(file.to_owned());
+                    }
+                }
+            });
+        });
+
+    if let Some(dir_path) = dir_to_open {
+        browser.set_dir(dir_path);
+    }
+
+    if let Some(file_path) = file_to_open {
+        if let Some(dir_path) = file_path.parent() {
+            settings.add_recent_dir(dir_path);
+            state.save_settings(settings);
+        }
+
+        state.add_file_event(AppFileEvent::Open(file_path));
+        open = false;
+    }
+
+    state.file_browser.open = open;
+}
+
+fn get_name(path: &Path) -> &str {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_else(|| path.to_str().unwrap_or_default())
+}
+
+fn is_known_file(path: &Path) -> bool {
+    get_file_type(get_name(path)).is_some()
+}
@@ -0,0 +1,129 @@
+use bevy_egui::{EguiContext, EguiPlugin, egui, egui::{Color32, Context, Pos2, Ui}};
+use super::{AppState, AppFileEvent};
+use crate::render::GltfExportSettings;
+use std::path::PathBuf;
+
+impl AppState {
+    pub fn show_export_gltf(&mut self) {
+        // Default to selected entry or milo name
+        let file_stem = self.milo_view.selected_entry
+            .as_ref()
+            .map(|e| e.split('.').next().unwrap_or(e).to_owned())
+            .or_else(|| self.open_ark_entry
+                .as_ref()
+                .and_then(|p| p.rsplit('/').next())
+                .or_else(|| self.open_file_path.as_ref().and_then(|p| p.file_stem()).and_then(|s| s.to_str()))
+                .map(|s| s.split('.').next().unwrap_or(s).to_owned()))
+            .unwrap_or_else(|| String::from("export"));
+
+        self.export_gltf.open = true;
+        self.export_gltf.path_text = format!("{file_stem}.glb");
+        self.export_gltf.selection_only = self.milo_view.selected_entry.is_some();
+    }
+}
+
+pub fn draw_export_gltf_window(ctx: &mut &Context, state: &mut AppState) {
+    if !state.export_gltf.open || state.milo.is_none() {
+        return;
+    }
+
+    let mut open = true;
+    let mut confirmed = false;
+
+    let selected_entry = state.milo_view.selected_entry.to_owned();
+    let view = &mut state.export_gltf;
+
+    egui::Window::new("Export glTF")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .open(&mut open)
+        .show(ctx, |ui| {
+            egui::Grid::new("export_gltf_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Path");
+                ui.add(egui::TextEdit::singleline(&mut view.path_text).desired_width(400.0));
+                ui.end_row();
+
+                ui.label("Objects");
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut view.selection_only, false, "Whole milo");
+
+                    let selection_text = match selected_entry.as_ref() {
+                        Some(entry) => format!("Selected ({entry})"),
+                        _ => String::from("Selected")
+                    };
+
+                    ui.add_enabled_ui(selected_entry.is_some(), |ui| {
+                        ui.radio_value(&mut view.selection_only, true, selection_text);
+                    });
+                });
+                ui.end_row();
+
+                ui.label("Textures");
+                ui.checkbox(&mut view.sidecar_textures, "Write as separate .png files")
+                    .on_hover_text("Otherwise textures are embedded in .glb");
+                ui.end_row();
+            });
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                let can_export = !view.path_text.trim().is_empty();
+                confirmed = ui.add_enabled(can_export, egui::Button::new("Export")).clicked();
+            });
+        });
+
+    if confirmed {
+        let settings = GltfExportSettings {
+            selected_entry: selected_entry.filter(|_| view.selection_only),
+            embed_textures: !view.sidecar_textures,
+        };
+
+        let glb_path = PathBuf::from(view.path_text.trim());
+        state.add_file_event(AppFileEvent::ExportGltf(glb_path, settings));
+
+        open = false;
+    }
+
+    state.export_gltf.open = open;
+}
+
+pub fn draw_import_model_window(ctx: &mut &Context, state: &mut AppState) {
+    if !state.import_model.open || state.milo.is_none() {
+        return;
+    }
+
+    let mut open = true;
+    let mut confirmed = false;
+
+    let view = &mut state.import_model;
+
+    egui::Window::new("Import Model")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Path");
+                ui.add(egui::TextEdit::singleline(&mut view.path_text).desired_width(400.0));
+            });
+
+            ui.label(egui::RichText::new("Supports .gltf, .glb and .obj").weak());
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                let can_import = !view.path_text.trim().is_empty();
+                confirmed = ui.add_enabled(can_import, egui::Button::new("Import")).clicked();
+            });
+        });
+
+    if confirmed {
+        let model_path = PathBuf::from(view.path_text.trim());
+        state.add_file_event(AppFileEvent::ImportModel(model_path));
+
+        open = false;
+    }
+
+    state.import_model.open = open;
+}
@@ -0,0 +1,76 @@
+use bevy_egui::{EguiContext, EguiPlugin, egui, egui::{Color32, Context, Pos2, Ui}};
+use super::{AppState, AppFileEvent};
+
+pub fn draw_history_window(ctx: &mut &Context, state: &mut AppState) {
+    if !state.show_history {
+        return;
+    }
+
+    let mut open = true;
+    let mut jump_depth = None;
+
+    let depth = state.history.get_depth();
+
+    egui::Window::new("History")
+        .default_size([240.0, 300.0])
+        .open(&mut open)
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical().auto_shrink(false).show(ui, |ui| {
+                if ui.selectable_label(depth == 0, "(Original)").clicked() {
+                    jump_depth = Some(0);
+                }
+
+                for (i, record) in state.history.get_records().enumerate() {
+                    let record_depth = i + 1;
+
+                    // Grey out undone edits
+                    let text = match record_depth > depth {
+                        true => egui::RichText::new(&record.description).weak(),
+                        _ => egui::RichText::new(&record.description)
+                    };
+
+                    if ui.selectable_label(record_depth == depth, text).clicked() {
+                        jump_depth = Some(record_depth);
+                    }
+                }
+            });
+        });
+
+    if let Some(depth) = jump_depth {
+        state.jump_to_history(depth);
+    }
+
+    state.show_history = open;
+}
+
+pub fn draw_unsaved_changes_window(ctx: &mut &Context, state: &mut AppState) {
+    let Some(action) = state.pending_close else {
+        return;
+    };
+
+    egui::Window::new("Unsaved Changes")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.label("Save changes before closing?");
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    state.pending_close = None;
+                    state.close_after_save = Some(action);
+                    state.add_file_event(AppFileEvent::Save);
+                }
+
+                if ui.button("Don't Save").clicked() {
+                    state.pending_close = None;
+                    state.add_event(action.into());
+                }
+
+                if ui.button("Cancel").clicked() {
+                    state.pending_close = None;
+                }
+            });
+        });
+}
@@ -33,6 +33,11 @@ pub static FA_EYE: LazyLock<RetainedImage> = LazyLock::new(|| egui_extras::Retai
     fa::svg(fa::Type::Solid, "eye").unwrap()
 ).unwrap());
 
+pub static FA_EYE_SLASH: LazyLock<RetainedImage> = LazyLock::new(|| egui_extras::RetainedImage::from_svg_str(
+    "fa_eye_slash",
+    fa::svg(fa::Type::Solid, "eye-slash").unwrap()
+).unwrap());
+
 pub static FA_TRASH: LazyLock<RetainedImage> = LazyLock::new(|| egui_extras::RetainedImage::from_svg_str(
     "fa_trash",
     fa::svg(fa::Type::Solid, "trash-can").unwrap()
@@ -57,4 +62,41 @@ pub static FA_SEARCH: LazyLock<RetainedImage> = LazyLock::new(|| egui_extras::Re
 pub static FA_CIRCLE: LazyLock<RetainedImage> = LazyLock::new(|| egui_extras::RetainedImage::from_svg_str(
     "fa_circle",
     fa::svg(fa::Type::Regular, "circle").unwrap()
-).unwrap());
\ No newline at end of file
+).unwrap());
+
+pub static FA_OBJECT_GROUP: LazyLock<RetainedImage> = LazyLock::new(|| egui_extras::RetainedImage::from_svg_str(
+    "fa_object_group",
+    fa::svg(fa::Type::Solid, "object-group").unwrap()
+).unwrap());
+
+pub static FA_CUBE: LazyLock<RetainedImage> = LazyLock::new(|| egui_extras::RetainedImage::from_svg_str(
+    "fa_cube",
+    fa::svg(fa::Type::Solid, "cube").unwrap()
+).unwrap());
+
+pub static FA_PALETTE: LazyLock<RetainedImage> = LazyLock::new(|| egui_extras::RetainedImage::from_svg_str(
+    "fa_palette",
+    fa::svg(fa::Type::Solid, "palette").unwrap()
+).unwrap());
+
+pub static FA_IMAGE: LazyLock<RetainedImage> = LazyLock::new(|| egui_extras::RetainedImage::from_svg_str(
+    "fa_image",
+    fa::svg(fa::Type::Solid, "image").unwrap()
+).unwrap());
+
+pub static FA_FILE: LazyLock<RetainedImage> = LazyLock::new(|| egui_extras::RetainedImage::from_svg_str(
+    "fa_file",
+    fa::svg(fa::Type::Solid, "file").unwrap()
+).unwrap());
+
+/// Icon for milo object type
+pub fn get_object_icon(obj_type: &str) -> &'static RetainedImage {
+    match obj_type {
+        "Group" => &FA_OBJECT_GROUP,
+        "Mesh" => &FA_CUBE,
+        "Trans" => &FA_ARROWS_MULTI,
+        "Mat" => &FA_PALETTE,
+        "Tex" => &FA_IMAGE,
+        _ => &FA_FILE,
+    }
+}
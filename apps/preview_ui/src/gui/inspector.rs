@@ -0,0 +1,439 @@
+use bevy_egui::{EguiContext, EguiPlugin, egui, egui::{Color32, Context, Pos2, Ui}};
+use pikaxe::scene::{Color3, GroupObject, MatObject, Matrix, MeshObject, MiloObject, Object, ObjectDir, Tex, Trans};
+use super::{AppState, EditCommand, PropertyValue, get_incoming_refs, get_outgoing_refs, is_dir_ref};
+use std::collections::HashMap;
+
+// Inspector output, applied once milo is no longer borrowed
+#[derive(Default)]
+struct InspectorActions {
+    edit: Option<(String, EditCommand, bool)>, // Description, command, merge
+    rename: Option<String>,
+    jump_to: Option<String>,
+    view_texture: Option<String>,
+}
+
+pub fn draw_inspector(ctx: &mut &Context, state: &mut AppState) {
+    let Some(milo) = state.milo.as_ref() else {
+        return;
+    };
+
+    let mut actions = InspectorActions::default();
+
+    egui::SidePanel::right("inspector_panel").min_width(200.0).default_width(280.0).resizable(true).show(ctx, |ui| {
+        ui.heading("Inspector");
+        ui.separator();
+
+        let obj = state.milo_view.selected_object
+            .as_ref()
+            .and_then(|name| milo.get_entries().iter().find(|e| e.get_name().eq(name)));
+
+        let Some(obj) = obj else {
+            ui.label("No object selected");
+            return;
+        };
+
+        let buffers = &mut state.milo_view.inspector_buffers;
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("inspector_common").num_columns(2).striped(true).show(ui, |ui| {
+                ui.label("Name");
+                if let Some(new_name) = edit_text(ui, "name", obj.get_name(), buffers) {
+                    actions.rename = Some(new_name);
+                }
+                ui.end_row();
+
+                ui.label("Type");
+                ui.label(obj.get_type());
+                ui.end_row();
+            });
+
+            let trans: Option<&dyn Trans> = match obj {
+                Object::Group(o) => Some(o),
+                Object::Mesh(o) => Some(o),
+                Object::Trans(o) => Some(o),
+                _ => None
+            };
+
+            if let Some(trans) = trans {
+                draw_trans(ui, obj.get_name(), trans, milo, buffers, &mut actions);
+            }
+
+            match obj {
+                Object::Group(group) => draw_group(ui, group, milo, buffers, &mut actions),
+                Object::Mat(mat) => draw_mat(ui, mat, milo, buffers, &mut actions),
+                Object::Mesh(mesh) => draw_mesh(ui, mesh, milo, buffers, &mut actions),
+                Object::Tex(tex) => draw_tex(ui, tex, buffers, &mut actions),
+                Object::Trans(_) => {},
+                _ => {
+                    ui.separator();
+                    ui.label(format!("No inspector for {}", obj.get_type()));
+                }
+            }
+
+            draw_references(ui, obj, milo, &mut actions);
+        });
+    });
+
+    let InspectorActions { edit, rename, jump_to, view_texture } = actions;
+    let selected_name = state.milo_view.selected_object.to_owned().unwrap_or_default();
+
+    if let Some((description, command, merge)) = edit {
+        state.execute_edit_merged(description, command, merge);
+    }
+
+    if let Some(new_name) = rename {
+        state.execute_edit(format!("Rename \"{selected_name}\" to \"{new_name}\""), EditCommand::rename(&selected_name, &new_name));
+        state.milo_view.selected_object = Some(new_name);
+    }
+
+    if let Some(name) = jump_to {
+        state.milo_view.selected_object = Some(name);
+        state.milo_view.scroll_to_selected = true;
+        state.milo_view.inspector_buffers.clear();
+    }
+
+    if let Some(tex_name) = view_texture {
+        state.view_texture(&tex_name);
+    }
+}
+
+fn draw_trans(ui: &mut Ui, name: &str, trans: &dyn Trans, milo: &ObjectDir, buffers: &mut HashMap<String, String>, actions: &mut InspectorActions) {
+    ui.separator();
+    ui.strong("Trans");
+
+    egui::Grid::new("inspector_trans").num_columns(2).striped(true).show(ui, |ui| {
+        ui.label("Parent");
+        ui.horizontal(|ui| {
+            if let Some(parent) = edit_text(ui, "parent", trans.get_parent(), buffers) {
+                actions.edit = Some((format!("Set parent of \"{name}\""), EditCommand::set_property(name, PropertyValue::Parent(parent)), false));
+            }
+
+            draw_jump_button(ui, trans.get_parent(), milo, actions);
+        });
+        ui.end_row();
+    });
+
+    // Local xfm is 4x3 (rotation rows + translation)
+    let mut values = matrix_to_array(trans.get_local_xfm());
+    let mut changed = false;
+    let mut dragging = false;
+
+    egui::CollapsingHeader::new("Local Xfm")
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::Grid::new("inspector_xfm").num_columns(4).show(ui, |ui| {
+                for (row, label) in ["X Axis", "Y Axis", "Z Axis", "Position"].iter().enumerate() {
+                    ui.label(*label);
+
+                    for col in 0..3 {
+                        let response = ui.add(egui::DragValue::new(&mut values[row * 4 + col]).speed(0.01).max_decimals(4));
+
+                        changed |= response.changed();
+                        dragging |= response.dragged() && !response.drag_started();
+                    }
+
+                    ui.end_row();
+                }
+            });
+        });
+
+    if changed {
+        actions.edit = Some((format!("Set local xfm of \"{name}\""), EditCommand::set_property(name, PropertyValue::LocalXfm(matrix_from_array(&values))), dragging));
+    }
+}
+
+fn draw_group(ui: &mut Ui, group: &GroupObject, milo: &ObjectDir, buffers: &mut HashMap<String, String>, actions: &mut InspectorActions) {
+    ui.separator();
+    ui.strong(format!("Members ({})", group.objects.len()));
+
+    let name = group.get_name();
+    let mut objects = None;
+
+    egui::Grid::new("inspector_group").num_columns(2).striped(true).show(ui, |ui| {
+        for (i, obj_name) in group.objects.iter().enumerate() {
+            draw_link(ui, obj_name, milo, actions);
+
+            if ui.small_button("x").on_hover_text("Remove from group").clicked() {
+                let mut new_objects = group.objects.iter().map(|o| o.to_owned()).collect::<Vec<_>>();
+                new_objects.remove(i);
+
+                objects = Some(new_objects);
+            }
+
+            ui.end_row();
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Add");
+
+        if let Some(obj_name) = edit_text(ui, "group_add", "", buffers).filter(|n| !n.is_empty()) {
+            let mut new_objects = group.objects.iter().map(|o| o.to_owned()).collect::<Vec<_>>();
+            new_objects.push(obj_name);
+
+            objects = Some(new_objects);
+        }
+    });
+
+    if let Some(objects) = objects {
+        actions.edit = Some((format!("Set members of \"{name}\""), EditCommand::set_property(name, PropertyValue::GroupObjects(objects)), false));
+    }
+}
+
+fn draw_mat(ui: &mut Ui, mat: &MatObject, milo: &ObjectDir, buffers: &mut HashMap<String, String>, actions: &mut InspectorActions) {
+    ui.separator();
+    ui.strong("Mat");
+
+    let name = mat.get_name();
+
+    egui::Grid::new("inspector_mat").num_columns(2).striped(true).show(ui, |ui| {
+        ui.label("Color");
+        let mut color = [mat.color.r, mat.color.g, mat.color.b];
+        let popup_id = ui.auto_id_with("popup"); // Same id used by color picker popup
+        let response = ui.color_edit_button_rgb(&mut color);
+        let popup_open = ui.memory(|mem| mem.is_popup_open(popup_id));
+        if response.changed() {
+            // Merge edits while picker stays open, first edit after opening starts new record
+            let merge = popup_open && buffers.contains_key("mat_color_editing");
+            if popup_open {
+                buffers.insert(String::from("mat_color_editing"), name.to_owned());
+            }
+
+            let [r, g, b] = color;
+            actions.edit = Some((format!("Set color of \"{name}\""), EditCommand::set_property(name, PropertyValue::Color(Color3 { r, g, b })), merge));
+        }
+        if !popup_open {
+            buffers.remove("mat_color_editing");
+        }
+        ui.end_row();
+
+        ui.label("Alpha");
+        let mut alpha = mat.alpha;
+        let response = ui.add(egui::Slider::new(&mut alpha, 0.0..=1.0));
+        if response.changed() {
+            let merge = response.dragged() && !response.drag_started();
+            actions.edit = Some((format!("Set alpha of \"{name}\""), EditCommand::set_property(name, PropertyValue::Alpha(alpha)), merge));
+        }
+        ui.end_row();
+
+        let tex_fields: [(&str, &str, fn(String) -> PropertyValue); 3] = [
+            ("Diffuse Tex", mat.diffuse_tex.as_str(), PropertyValue::DiffuseTex),
+            ("Normal Map", mat.normal_map.as_str(), PropertyValue::NormalMap),
+            ("Emissive Map", mat.emissive_map.as_str(), PropertyValue::EmissiveMap),
+        ];
+
+        for (label, tex_name, to_value) in tex_fields {
+            ui.label(label);
+            ui.horizontal(|ui| {
+                if let Some(new_name) = edit_text(ui, label, tex_name, buffers) {
+                    actions.edit = Some((format!("Set {} of \"{name}\"", label.to_ascii_lowercase()), EditCommand::set_property(name, to_value(new_name)), false));
+                }
+
+                draw_jump_button(ui, tex_name, milo, actions);
+            });
+            ui.end_row();
+        }
+    });
+}
+
+fn draw_mesh(ui: &mut Ui, mesh: &MeshObject, milo: &ObjectDir, buffers: &mut HashMap<String, String>, actions: &mut InspectorActions) {
+    ui.separator();
+    ui.strong("Mesh");
+
+    let name = mesh.get_name();
+
+    egui::Grid::new("inspector_mesh").num_columns(2).striped(true).show(ui, |ui| {
+        ui.label("Mat");
+        ui.horizontal(|ui| {
+            if let Some(mat) = edit_text(ui, "mesh_mat", &mesh.mat, buffers) {
+                actions.edit = Some((format!("Set mat of \"{name}\""), EditCommand::set_property(name, PropertyValue::MeshMat(mat)), false));
+            }
+
+            draw_jump_button(ui, &mesh.mat, milo, actions);
+        });
+        ui.end_row();
+
+        ui.label("Vertices");
+        ui.label(mesh.vertices.len().to_string());
+        ui.end_row();
+
+        ui.label("Faces");
+        ui.label(mesh.faces.len().to_string());
+        ui.end_row();
+    });
+
+    if !mesh.draw_objects.is_empty() {
+        ui.strong(format!("Draw Objects ({})", mesh.draw_objects.len()));
+
+        for obj_name in mesh.draw_objects.iter() {
+            draw_link(ui, obj_name, milo, actions);
+        }
+    }
+}
+
+fn draw_tex(ui: &mut Ui, tex: &Tex, buffers: &mut HashMap<String, String>, actions: &mut InspectorActions) {
+    ui.separator();
+    ui.strong("Tex");
+
+    let name = tex.get_name();
+
+    egui::Grid::new("inspector_tex").num_columns(2).striped(true).show(ui, |ui| {
+        ui.label("Size");
+        ui.label(format!("{}x{}", tex.width, tex.height));
+        ui.end_row();
+
+        ui.label("Bpp");
+        ui.label(tex.bpp.to_string());
+        ui.end_row();
+
+        ui.label("Ext Path");
+        if let Some(ext_path) = edit_text(ui, "ext_path", &tex.ext_path, buffers) {
+            actions.edit = Some((format!("Set ext path of \"{name}\""), EditCommand::set_property(name, PropertyValue::ExtPath(ext_path)), false));
+        }
+        ui.end_row();
+
+        ui.label("Bitmap");
+        if ui.add_enabled(tex.bitmap.is_some(), egui::Button::new("View")).clicked() {
+            actions.view_texture = Some(name.to_owned());
+        }
+        ui.end_row();
+
+        let Some(bitmap) = tex.bitmap.as_ref() else {
+            return;
+        };
+
+        ui.label("Bitmap Size");
+        ui.label(format!("{}x{}", bitmap.width, bitmap.height));
+        ui.end_row();
+
+        ui.label("Bitmap Bpp");
+        ui.label(bitmap.bpp.to_string());
+        ui.end_row();
+
+        ui.label("Encoding");
+        ui.label(bitmap.encoding.to_string());
+        ui.end_row();
+
+        ui.label("Mip Maps");
+        ui.label(bitmap.mip_maps.to_string());
+        ui.end_row();
+
+        ui.label("Data Size");
+        ui.label(format!("{} bytes", bitmap.raw_data.len()));
+        ui.end_row();
+    });
+}
+
+fn draw_references(ui: &mut Ui, obj: &Object, milo: &ObjectDir, actions: &mut InspectorActions) {
+    let outgoing = get_outgoing_refs(obj);
+    let incoming = get_incoming_refs(milo, obj.get_name());
+
+    ui.separator();
+
+    egui::CollapsingHeader::new(format!("References ({})", outgoing.len()))
+        .id_salt("inspector_refs_out")
+        .show(ui, |ui| {
+            egui::Grid::new("inspector_refs_out_grid").num_columns(2).striped(true).show(ui, |ui| {
+                for obj_ref in outgoing.iter() {
+                    ui.label(obj_ref.kind.label());
+
+                    match is_dir_ref(milo, obj_ref) {
+                        true => {
+                            ui.label(obj_ref.to).on_hover_text("Milo dir");
+                        },
+                        _ => draw_link(ui, obj_ref.to, milo, actions)
+                    }
+
+                    ui.end_row();
+                }
+            });
+        });
+
+    egui::CollapsingHeader::new(format!("Referenced By ({})", incoming.len()))
+        .id_salt("inspector_refs_in")
+        .show(ui, |ui| {
+            egui::Grid::new("inspector_refs_in_grid").num_columns(2).striped(true).show(ui, |ui| {
+                for obj_ref in incoming.iter() {
+                    draw_link(ui, obj_ref.from, milo, actions);
+                    ui.label(obj_ref.kind.label());
+                    ui.end_row();
+                }
+            });
+        });
+}
+
+fn draw_link(ui: &mut Ui, obj_name: &str, milo: &ObjectDir, actions: &mut InspectorActions) {
+    match find_object(milo, obj_name) {
+        true => if ui.link(obj_name).clicked() {
+            actions.jump_to = Some(obj_name.to_owned());
+        },
+        _ => {
+            ui.label(egui::RichText::new(obj_name).color(Color32::RED))
+                .on_hover_text("Object not found");
+        }
+    }
+}
+
+fn draw_jump_button(ui: &mut Ui, obj_name: &str, milo: &ObjectDir, actions: &mut InspectorActions) {
+    if obj_name.is_empty() {
+        return;
+    }
+
+    let found = find_object(milo, obj_name);
+
+    let response = ui.add_enabled(found, egui::Button::new("Go").small());
+    let response = match found {
+        true => response.on_hover_text(format!("Go to \"{obj_name}\"")),
+        _ => response.on_disabled_hover_text("Object not found")
+    };
+
+    if response.clicked() {
+        actions.jump_to = Some(obj_name.to_owned());
+    }
+}
+
+/// Text field that only returns new value once editing is done
+fn edit_text(ui: &mut Ui, id: &str, value: &str, buffers: &mut HashMap<String, String>) -> Option<String> {
+    let buffer = buffers
+        .entry(id.to_owned())
+        .or_insert_with(|| value.to_owned());
+
+    let response = ui.text_edit_singleline(buffer);
+
+    if response.lost_focus() {
+        let new_value = buffers.remove(id).unwrap_or_default();
+
+        if new_value.ne(value) {
+            return Some(new_value);
+        }
+    } else if !response.has_focus() {
+        // Keep in sync with undo/redo
+        *buffer = value.to_owned();
+    }
+
+    None
+}
+
+fn find_object(milo: &ObjectDir, obj_name: &str) -> bool {
+    milo.get_entries()
+        .iter()
+        .any(|e| e.get_name().eq(obj_name))
+}
+
+fn matrix_to_array(m: &Matrix) -> [f32; 16] {
+    [
+        m.m11, m.m12, m.m13, m.m14,
+        m.m21, m.m22, m.m23, m.m24,
+        m.m31, m.m32, m.m33, m.m34,
+        m.m41, m.m42, m.m43, m.m44,
+    ]
+}
+
+fn matrix_from_array(v: &[f32; 16]) -> Matrix {
+    Matrix {
+        m11: v[0], m12: v[1], m13: v[2], m14: v[3],
+        m21: v[4], m22: v[5], m23: v[6], m24: v[7],
+        m31: v[8], m32: v[9], m33: v[10], m34: v[11],
+        m41: v[12], m42: v[13], m43: v[14], m44: v[15],
+    }
+}
@@ -1,9 +1,20 @@
 use bevy_egui::{EguiContext, EguiPlugin, egui, egui::{Color32, Context, Pos2, Ui}};
 use pikaxe::ark::{Ark, ArkOffsetEntry};
 use itertools::*;
-use super::{AppSettings, AppState, ArkDirNode, AppEvent};
+use super::{AppSettings, AppState, ArkDirNode, AppEvent, EditCommand, draw_milo_scene_tree};
+
+pub fn draw_milo_tree(state: &mut AppState, ctx: &mut &Context, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        ui.label("View:");
+        ui.selectable_value(&mut state.milo_view.tree_mode, false, "List");
+        ui.selectable_value(&mut state.milo_view.tree_mode, true, "Tree");
+    });
+
+    if state.milo_view.tree_mode {
+        draw_milo_scene_tree(state, ctx, ui);
+        return;
+    }
 
-pub fn draw_milo_tree(state: &mut AppState, _ctx: &mut &Context, ui: &mut Ui) {
     if let Some(milo) = state.milo.take() {
         let mut entries = milo.get_entries().iter().map(|e| e).collect::<Vec<_>>();
 
@@ -47,6 +58,8 @@ pub fn draw_milo_tree(state: &mut AppState, _ctx: &mut &Context, ui: &mut Ui) {
         // Sort objects
         entries.sort_by_key(|e| e.get_name());
 
+        let mut edit = None;
+
         egui::Grid::new("milo_tree").min_col_width(200.0).striped(true).show(ui, |ui| {
             for entry in entries.iter() {
                 let entry_name = entry.get_name();
@@ -56,9 +69,53 @@ pub fn draw_milo_tree(state: &mut AppState, _ctx: &mut &Context, ui: &mut Ui) {
                     checked = selected.eq(entry_name);
                 }
 
-                if ui.selectable_label(checked, entry_name).clicked() {
+                let picked = state.milo_view.selected_object
+                    .as_ref()
+                    .is_some_and(|o| o.eq(entry_name));
+
+                if state.milo_view.renaming_entry.as_ref().is_some_and(|r| r.eq(entry_name)) {
+                    let response = ui.text_edit_singleline(&mut state.milo_view.rename_text);
+                    response.request_focus();
+
+                    if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                        state.milo_view.renaming_entry = None;
+                    } else if response.lost_focus() {
+                        let new_name = state.milo_view.rename_text.trim().to_owned();
+                        state.milo_view.renaming_entry = None;
+
+                        if !new_name.is_empty() && new_name.ne(entry_name) {
+                            edit = Some((format!("Rename \"{entry_name}\" to \"{new_name}\""), EditCommand::rename(entry_name, &new_name)));
+                        }
+                    }
+
+                    ui.end_row();
+                    continue;
+                }
+
+                let response = ui.selectable_label(checked || picked, entry_name);
+
+                if picked && state.milo_view.scroll_to_selected {
+                    response.scroll_to_me(None);
+                    state.milo_view.scroll_to_selected = false;
+                }
+
+                if response.clicked() {
+                    state.milo_view.selected_object = Some(entry_name.to_owned());
                     state.add_event(AppEvent::SelectMiloEntry(Some(entry_name.to_owned())));
                 }
+
+                response.context_menu(|ui| {
+                    if ui.button("Rename").clicked() {
+                        state.milo_view.renaming_entry = Some(entry_name.to_owned());
+                        state.milo_view.rename_text = entry_name.to_owned();
+                        ui.close_menu();
+                    }
+
+                    if ui.button("Delete").clicked() {
+                        edit = Some((format!("Delete \"{entry_name}\""), EditCommand::delete(vec![entry_name.to_owned()])));
+                        ui.close_menu();
+                    }
+                });
                 ui.end_row();
             }
 
@@ -69,5 +126,10 @@ pub fn draw_milo_tree(state: &mut AppState, _ctx: &mut &Context, ui: &mut Ui) {
 
         // Give milo back
         state.milo = Some(milo);
+
+        // Apply after milo is returned
+        if let Some((description, command)) = edit {
+            state.execute_edit(description, command);
+        }
     }
 }
\ No newline at end of file
@@ -1,12 +1,28 @@
 mod ark;
+mod browser;
+mod export;
+mod history;
+mod inspector;
 mod icons;
 mod milo;
+mod references;
+mod save;
+mod scene;
+mod texture;
 mod toolbar;
 
 use ark::*;
+use browser::*;
+use export::*;
+use history::*;
+use inspector::*;
 use bevy_egui::{EguiContext, EguiPlugin, egui, egui::{Color32, Context, Pos2, Ui}};
 use milo::*;
-use super::{AppSettings, AppState, ArkDirNode, AppEvent};
+use references::*;
+use save::*;
+use scene::*;
+use texture::*;
+use super::{AppSettings, AppState, ArkAction, ArkDirNode, AppEvent, AppFileEvent, CloseAction, EditCommand, FileBrowserView, PropertyValue, SideBarTab, TextureChannel};
 use toolbar::*;
 
 pub fn render_gui(ctx: &mut &Context, settings: &mut AppSettings, state: &mut AppState) {
@@ -23,17 +39,17 @@ pub fn render_gui(ctx: &mut &Context, settings: &mut AppSettings, state: &mut Ap
 
                 ui.vertical(|ui| {
                     ui.horizontal(|ui| {
-                        for (i, text) in [ "Ark", "Milo" ].iter().enumerate() {
-                            if ui.selectable_label(i == state.side_bar_tab_index, *text).clicked() {
-                                state.side_bar_tab_index = i;
+                        for tab in SideBarTab::ALL {
+                            if ui.selectable_label(tab == state.side_bar_tab, tab.label()).clicked() {
+                                state.side_bar_tab = tab;
                             }
                         }
                     });
 
-                    match state.side_bar_tab_index {
-                        0 => draw_ark_tree(state, ctx, ui),
-                        1 => draw_milo_tree(state, ctx, ui),
-                        _ => todo!()
+                    match state.side_bar_tab {
+                        SideBarTab::Ark => draw_ark_tree(state, ctx, ui),
+                        SideBarTab::Milo => draw_milo_tree(state, ctx, ui),
+                        SideBarTab::Texture => draw_texture_view(state, ctx, ui),
                     };
 
                     /*ui.group(|ui| {
@@ -72,6 +88,9 @@ pub fn render_gui(ctx: &mut &Context, settings: &mut AppSettings, state: &mut Ap
         });
     });
 
+    // Selected object properties
+    draw_inspector(ctx, state);
+
     /*let mut frame = egui::Frame::default();
     frame.fill = Color32::from_rgba_premultiplied(0, 128, 128, 16);
 
@@ -96,6 +115,26 @@ pub fn render_gui(ctx: &mut &Context, settings: &mut AppSettings, state: &mut Ap
         ui.label("world");
     });*/
 
+    // Ark extract/replace prompt
+    draw_ark_action_window(state, ctx);
+
+    // File > Open
+    draw_file_browser(ctx, settings, state);
+
+    // File > Save As
+    draw_save_as_window(ctx, state);
+
+    // File > Export glTF + Import Model
+    draw_export_gltf_window(ctx, state);
+    draw_import_model_window(ctx, state);
+
+    // Edit history + unsaved changes prompt
+    draw_history_window(ctx, state);
+    draw_unsaved_changes_window(ctx, state);
+
+    // View > References
+    draw_references_window(ctx, state);
+
     let size = ctx.used_size();
     let _size_pos = Pos2::new(size.x, size.y);
 
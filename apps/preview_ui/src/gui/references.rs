@@ -0,0 +1,160 @@
+use bevy_egui::{EguiContext, EguiPlugin, egui, egui::{Color32, Context, Pos2, Ui}};
+use pikaxe::scene::{MiloObject, Object, ObjectDir, Trans};
+use super::AppState;
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum RefKind {
+    Parent,
+    Member,
+    Mat,
+    DrawObject,
+    DiffuseTex,
+    NormalMap,
+    EmissiveMap,
+}
+
+impl RefKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RefKind::Parent => "Parent",
+            RefKind::Member => "Member",
+            RefKind::Mat => "Mat",
+            RefKind::DrawObject => "Draw Object",
+            RefKind::DiffuseTex => "Diffuse Tex",
+            RefKind::NormalMap => "Normal Map",
+            RefKind::EmissiveMap => "Emissive Map",
+        }
+    }
+}
+
+/// Reference by name from one object to another
+pub struct ObjectRef<'a> {
+    pub from: &'a str,
+    pub to: &'a str,
+    pub kind: RefKind,
+}
+
+/// Objects referenced by given object (empty names skipped)
+pub fn get_outgoing_refs(obj: &Object) -> Vec<ObjectRef<'_>> {
+    let from = obj.get_name().as_str();
+    let mut refs = Vec::new();
+
+    let parent = match obj {
+        Object::Group(grp) => Some(grp.get_parent().as_str()),
+        Object::Mesh(mesh) => Some(mesh.get_parent().as_str()),
+        Object::Trans(trans) => Some(trans.get_parent().as_str()),
+        _ => None,
+    };
+
+    // Parent can reference self
+    if let Some(parent) = parent.filter(|p| p.ne(&from)) {
+        refs.push(ObjectRef { from, to: parent, kind: RefKind::Parent });
+    }
+
+    match obj {
+        Object::Group(grp) => {
+            refs.extend(grp.objects.iter().map(|o| ObjectRef { from, to: o, kind: RefKind::Member }));
+        },
+        Object::Mat(mat) => {
+            refs.push(ObjectRef { from, to: &mat.diffuse_tex, kind: RefKind::DiffuseTex });
+            refs.push(ObjectRef { from, to: &mat.normal_map, kind: RefKind::NormalMap });
+            refs.push(ObjectRef { from, to: &mat.emissive_map, kind: RefKind::EmissiveMap });
+        },
+        Object::Mesh(mesh) => {
+            refs.push(ObjectRef { from, to: &mesh.mat, kind: RefKind::Mat });
+            refs.extend(mesh.draw_objects.iter().map(|o| ObjectRef { from, to: o, kind: RefKind::DrawObject }));
+        },
+        _ => {}
+    }
+
+    refs.retain(|r| !r.to.is_empty());
+    refs
+}
+
+/// Objects that reference given object name
+pub fn get_incoming_refs<'a>(milo: &'a ObjectDir, obj_name: &str) -> Vec<ObjectRef<'a>> {
+    milo.get_entries()
+        .iter()
+        .flat_map(get_outgoing_refs)
+        .filter(|r| r.to.eq(obj_name))
+        .collect()
+}
+
+/// References to objects missing from milo
+pub fn get_broken_refs(milo: &ObjectDir) -> Vec<ObjectRef<'_>> {
+    let names = milo.get_entries()
+        .iter()
+        .map(|e| e.get_name().as_str())
+        .collect::<HashSet<_>>();
+
+    milo.get_entries()
+        .iter()
+        .flat_map(get_outgoing_refs)
+        .filter(|r| !names.contains(r.to) && !is_dir_ref(milo, r))
+        .collect()
+}
+
+/// Root objects are usually parented to milo dir itself
+pub fn is_dir_ref(milo: &ObjectDir, obj_ref: &ObjectRef) -> bool {
+    let dir_name = match milo {
+        ObjectDir::ObjectDir(dir) => &dir.name
+    };
+
+    obj_ref.kind == RefKind::Parent && obj_ref.to.eq(dir_name)
+}
+
+pub fn draw_references_window(ctx: &mut &Context, state: &mut AppState) {
+    if !state.show_references {
+        return;
+    }
+
+    let Some(milo) = state.milo.as_ref() else {
+        return;
+    };
+
+    let mut open = true;
+    let mut jump_to = None;
+
+    let broken_refs = get_broken_refs(milo);
+
+    egui::Window::new("Broken References")
+        .default_size([360.0, 300.0])
+        .open(&mut open)
+        .show(ctx, |ui| {
+            if broken_refs.is_empty() {
+                ui.label("No broken references");
+                return;
+            }
+
+            ui.label(format!("Found {} broken reference(s)", broken_refs.len()));
+            ui.separator();
+
+            egui::ScrollArea::vertical().auto_shrink(false).show(ui, |ui| {
+                egui::Grid::new("broken_refs_grid").num_columns(3).striped(true).show(ui, |ui| {
+                    ui.strong("Object");
+                    ui.strong("Field");
+                    ui.strong("Missing");
+                    ui.end_row();
+
+                    for obj_ref in broken_refs.iter() {
+                        if ui.link(obj_ref.from).on_hover_text("Select to fix in inspector").clicked() {
+                            jump_to = Some(obj_ref.from.to_owned());
+                        }
+
+                        ui.label(obj_ref.kind.label());
+                        ui.label(egui::RichText::new(obj_ref.to).color(Color32::RED));
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+
+    if let Some(name) = jump_to {
+        state.milo_view.selected_object = Some(name);
+        state.milo_view.scroll_to_selected = true;
+        state.milo_view.inspector_buffers.clear();
+    }
+
+    state.show_references = open;
+}
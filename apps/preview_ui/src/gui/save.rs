@@ -0,0 +1,137 @@
+use bevy_egui::{EguiContext, EguiPlugin, egui, egui::{Color32, Context, Pos2, Ui}};
+use pikaxe::{Platform, SystemInfo};
+use pikaxe::io::{BlockType, IOEndian};
+use super::{AppState, AppFileEvent};
+use std::path::PathBuf;
+
+impl AppState {
+    pub fn can_save_milo(&self) -> bool {
+        self.milo.is_some() && self.system_info.is_some()
+    }
+
+    /// Path of milo opened from disk (i.e. not ark entry)
+    pub fn get_milo_file_path(&self) -> Option<&PathBuf> {
+        if self.open_ark_entry.is_some() || !self.can_save_milo() {
+            return None;
+        }
+
+        self.open_file_path
+            .as_ref()
+            .filter(|p| !self.ark.as_ref().is_some_and(|a| a.path.eq(*p)))
+    }
+
+    pub fn show_save_as(&mut self) {
+        // Default to original platform + compression
+        let milo_path = self.open_ark_entry
+            .as_ref()
+            .map(|p| p.trim_start_matches("./").replace('/', "_"))
+            .map(PathBuf::from)
+            .or_else(|| self.get_milo_file_path().cloned())
+            .unwrap_or_default();
+
+        self.save_as.open = true;
+        self.save_as.path_text = milo_path.to_string_lossy().to_string();
+        self.save_as.platform = self.system_info.as_ref().map(|i| i.platform);
+        self.save_as.block_type = self.milo_block_type.to_owned();
+    }
+}
+
+pub fn draw_save_as_window(ctx: &mut &Context, state: &mut AppState) {
+    if !state.save_as.open || !state.can_save_milo() {
+        return;
+    }
+
+    let mut open = true;
+    let mut confirmed = false;
+
+    let view = &mut state.save_as;
+
+    egui::Window::new("Save As")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .open(&mut open)
+        .show(ctx, |ui| {
+            egui::Grid::new("save_as_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Path");
+                ui.add(egui::TextEdit::singleline(&mut view.path_text).desired_width(400.0));
+                ui.end_row();
+
+                ui.label("Platform");
+                egui::ComboBox::from_id_salt("save_as_platform")
+                    .selected_text(view.platform.as_ref().map(get_platform_name).unwrap_or("(None)"))
+                    .show_ui(ui, |ui| {
+                        for platform in [Platform::PS2, Platform::PS3, Platform::Wii, Platform::X360] {
+                            let name = get_platform_name(&platform);
+                            ui.selectable_value(&mut view.platform, Some(platform), name);
+                        }
+                    })
+                    .response
+                    .on_hover_text("Textures are re-encoded for new platform");
+                ui.end_row();
+
+                ui.label("Compression");
+                egui::ComboBox::from_id_salt("save_as_compression")
+                    .selected_text(view.block_type.as_ref().map(get_block_type_name).unwrap_or("(None)"))
+                    .show_ui(ui, |ui| {
+                        for block_type in [BlockType::TypeA, BlockType::TypeB, BlockType::TypeC, BlockType::TypeD] {
+                            let name = get_block_type_name(&block_type);
+                            ui.selectable_value(&mut view.block_type, Some(block_type), name);
+                        }
+                    });
+                ui.end_row();
+            });
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                let can_save = view.platform.is_some() && view.block_type.is_some();
+                confirmed = ui.add_enabled(can_save, egui::Button::new("Save")).clicked();
+            });
+        });
+
+    if confirmed {
+        let platform = view.platform.take().unwrap();
+        let version = state.system_info.as_ref().unwrap().version;
+
+        let system_info = SystemInfo {
+            version,
+            endian: match platform {
+                Platform::PS2 => IOEndian::Little,
+                _ => IOEndian::Big
+            },
+            platform,
+        };
+
+        state.add_file_event(AppFileEvent::SaveAs(
+            PathBuf::from(state.save_as.path_text.trim()),
+            system_info,
+            state.save_as.block_type.take().unwrap()
+        ));
+
+        open = false;
+    } else if !open {
+        // Cancelled, don't continue exit/close from unsaved changes prompt
+        state.close_after_save = None;
+    }
+
+    state.save_as.open = open;
+}
+
+pub fn get_platform_name(platform: &Platform) -> &'static str {
+    match platform {
+        Platform::PS2 => "PS2",
+        Platform::PS3 => "PS3",
+        Platform::Wii => "Wii",
+        _ => "Xbox 360",
+    }
+}
+
+fn get_block_type_name(block_type: &BlockType) -> &'static str {
+    match block_type {
+        BlockType::TypeA => "Uncompressed",
+        BlockType::TypeB => "Zlib",
+        BlockType::TypeC => "Gzip",
+        BlockType::TypeD => "Zlib (sized blocks)",
+    }
+}
@@ -0,0 +1,242 @@
+use bevy_egui::{EguiContext, EguiPlugin, egui, egui::{Color32, Context, Pos2, Ui}};
+use pikaxe::scene::{MiloObject, Object, Trans};
+use itertools::*;
+use std::collections::{HashMap, HashSet};
+use super::{AppState, AppEvent, icons};
+
+const ICON_SIZE: egui::Vec2 = egui::Vec2::splat(12.);
+
+struct SceneTree<'a> {
+    objects: HashMap<&'a str, &'a Object>,
+    children: HashMap<&'a str, Vec<&'a str>>,
+    roots: Vec<&'a str>, // Trans roots
+    others: Vec<&'a str>, // Mats, texs, etc.
+}
+
+struct SceneTreeView<'a> {
+    tree: SceneTree<'a>,
+    selected_entry: Option<&'a str>,
+    selected_object: Option<&'a str>,
+}
+
+pub fn draw_milo_scene_tree(state: &mut AppState, ctx: &mut &Context, ui: &mut Ui) {
+    let Some(milo) = state.milo.take() else {
+        return;
+    };
+
+    let tree = build_scene_tree(milo.get_entries());
+
+    let view = SceneTreeView {
+        tree,
+        selected_entry: state.milo_view.selected_entry.as_deref(),
+        selected_object: state.milo_view.selected_object.as_deref(),
+    };
+
+    let mut clicked = None;
+    let mut toggled = None;
+
+    egui::ScrollArea::vertical().auto_shrink(false).show(ui, |ui| {
+        let hidden = &state.milo_view.hidden_objects;
+
+        for root in view.tree.roots.iter() {
+            draw_node(*root, &view, hidden, false, &mut Vec::new(), &mut clicked, &mut toggled, ctx, ui);
+        }
+
+        if !view.tree.others.is_empty() {
+            egui::CollapsingHeader::new(format!("Other ({})", view.tree.others.len()))
+                .id_salt("scene_tree_others")
+                .default_open(false)
+                .show(ui, |ui| {
+                    for other in view.tree.others.iter() {
+                        draw_node(*other, &view, hidden, false, &mut Vec::new(), &mut clicked, &mut toggled, ctx, ui);
+                    }
+                });
+        }
+
+        if view.tree.objects.is_empty() {
+            ui.label("No objects found");
+        }
+    });
+
+    if let Some(name) = toggled {
+        let hidden = &mut state.milo_view.hidden_objects;
+
+        if !hidden.remove(&name) {
+            hidden.insert(name);
+        }
+    }
+
+    // Collapsed nodes aren't drawn so check whole tree
+    state.milo_view.hidden_meshes = get_hidden_meshes(&view.tree, &state.milo_view.hidden_objects);
+
+    // Give milo back
+    state.milo = Some(milo);
+
+    if let Some(name) = clicked {
+        state.milo_view.selected_object = Some(name.to_owned());
+        state.add_event(AppEvent::SelectMiloEntry(Some(name)));
+    }
+}
+
+fn draw_node<'a>(name: &'a str, view: &SceneTreeView<'a>, hidden: &HashSet<String>, parent_hidden: bool, path: &mut Vec<&'a str>, clicked: &mut Option<String>, toggled: &mut Option<String>, ctx: &mut &Context, ui: &mut Ui) {
+    let Some(obj) = view.tree.objects.get(name) else {
+        return;
+    };
+
+    let is_hidden = parent_hidden || hidden.contains(name);
+    let is_selected = view.selected_entry.is_some_and(|s| s.eq(name))
+        || view.selected_object.is_some_and(|s| s.eq(name));
+
+    // Objects can reference each other in a loop
+    let children = view.tree.children
+        .get(name)
+        .filter(|_| !path.contains(&name));
+
+    let draw_row = |ui: &mut Ui, clicked: &mut Option<String>, toggled: &mut Option<String>| {
+        let eye = match is_hidden {
+            true => &icons::FA_EYE_SLASH,
+            _ => &icons::FA_EYE,
+        };
+
+        let eye_response = ui.add(egui::ImageButton::new(egui::load::SizedTexture::new(eye.texture_id(ctx), ICON_SIZE)).frame(false))
+            .on_hover_text(if hidden.contains(name) { "Show" } else { "Hide" });
+
+        if eye_response.clicked() {
+            *toggled = Some(name.to_owned());
+        }
+
+        ui.image(egui::load::SizedTexture::new(icons::get_object_icon(obj.get_type()).texture_id(ctx), ICON_SIZE))
+            .on_hover_text(obj.get_type());
+
+        let text = match is_hidden {
+            true => egui::RichText::new(name).weak(),
+            _ => egui::RichText::new(name)
+        };
+
+        if ui.selectable_label(is_selected, text).clicked() {
+            *clicked = Some(name.to_owned());
+        }
+    };
+
+    let Some(children) = children else {
+        ui.horizontal(|ui| {
+            // Line up with collapsible rows
+            ui.add_space(ui.spacing().icon_width + ui.spacing().item_spacing.x);
+            draw_row(ui, clicked, toggled);
+        });
+
+        return;
+    };
+
+    let id = ui.make_persistent_id(("scene_node", name));
+
+    egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+        .show_header(ui, |ui| draw_row(ui, clicked, toggled))
+        .body(|ui| {
+            path.push(name);
+
+            for child in children.iter() {
+                draw_node(*child, view, hidden, is_hidden, path, clicked, toggled, ctx, ui);
+            }
+
+            path.pop();
+        });
+}
+
+fn build_scene_tree(entries: &[Object]) -> SceneTree<'_> {
+    let objects = entries
+        .iter()
+        .map(|e| (e.get_name().as_str(), e))
+        .collect::<HashMap<_, _>>();
+
+    let mut children: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut has_parent = HashSet::new();
+
+    for entry in entries.iter() {
+        let name = entry.get_name().as_str();
+
+        // Trans parent
+        if let Some(trans) = get_trans(entry) {
+            let parent = trans.get_parent().as_str();
+
+            if parent.ne(name) && objects.contains_key(parent) {
+                children.entry(parent).or_default().push(name);
+                has_parent.insert(name);
+            }
+        }
+
+        // Group members + mesh draw objects
+        let sub_names = match entry {
+            Object::Group(grp) => grp.objects.as_slice(),
+            Object::Mesh(mesh) => mesh.draw_objects.as_slice(),
+            _ => &[],
+        };
+
+        for sub_name in sub_names.iter().map(|s| s.as_str()).filter(|s| s.ne(&name) && objects.contains_key(s)) {
+            children.entry(name).or_default().push(sub_name);
+            has_parent.insert(sub_name);
+        }
+    }
+
+    for names in children.values_mut() {
+        names.sort();
+        names.dedup();
+    }
+
+    let (roots, others) = entries
+        .iter()
+        .filter(|e| !has_parent.contains(e.get_name().as_str()))
+        .sorted_by_key(|e| e.get_name())
+        .partition::<Vec<_>, _>(|e| get_trans(e).is_some());
+
+    SceneTree {
+        objects,
+        children,
+        roots: roots.into_iter().map(|e| e.get_name().as_str()).collect(),
+        others: others.into_iter().map(|e| e.get_name().as_str()).collect(),
+    }
+}
+
+fn get_hidden_meshes(tree: &SceneTree, hidden: &HashSet<String>) -> HashSet<String> {
+    let mut hidden_meshes = HashSet::new();
+
+    if hidden.is_empty() {
+        return hidden_meshes;
+    }
+
+    let mut stack = tree.roots
+        .iter()
+        .chain(tree.others.iter())
+        .map(|r| (*r, false))
+        .collect::<Vec<_>>();
+
+    let mut visited = HashSet::new();
+
+    while let Some((name, parent_hidden)) = stack.pop() {
+        let is_hidden = parent_hidden || hidden.contains(name);
+
+        // Visit again if hidden through another path
+        if !visited.insert((name, is_hidden)) {
+            continue;
+        }
+
+        if is_hidden && matches!(tree.objects.get(name), Some(Object::Mesh(_))) {
+            hidden_meshes.insert(name.to_owned());
+        }
+
+        for child in tree.children.get(name).into_iter().flatten() {
+            stack.push((*child, is_hidden));
+        }
+    }
+
+    hidden_meshes
+}
+
+fn get_trans(obj: &Object) -> Option<&dyn Trans> {
+    match obj {
+        Object::Group(grp) => Some(grp as &dyn Trans),
+        Object::Mesh(mesh) => Some(mesh as &dyn Trans),
+        Object::Trans(trans) => Some(trans as &dyn Trans),
+        _ => None,
+    }
+}
@@ -0,0 +1,212 @@
+use bevy_egui::{EguiContext, EguiPlugin, egui, egui::{Color32, Context, Pos2, Ui}};
+use pikaxe::scene::{MiloObject, Object};
+use super::{AppState, AppFileEvent, TextureChannel, get_platform_name};
+use crate::render::{decode_bitmap, DecodedMip};
+use itertools::*;
+
+const CHANNELS: [(TextureChannel, &str); 6] = [
+    (TextureChannel::RGBA, "RGBA"),
+    (TextureChannel::RGB, "RGB"),
+    (TextureChannel::R, "R"),
+    (TextureChannel::G, "G"),
+    (TextureChannel::B, "B"),
+    (TextureChannel::A, "A"),
+];
+
+pub fn draw_texture_view(state: &mut AppState, ctx: &mut &Context, ui: &mut Ui) {
+    let Some(milo) = state.milo.as_ref() else {
+        ui.label("No milo open");
+        return;
+    };
+
+    let tex_names = milo
+        .get_entries()
+        .iter()
+        .filter(|e| e.get_type().eq("Tex"))
+        .map(|e| e.get_name().to_owned())
+        .sorted()
+        .collect::<Vec<_>>();
+
+    // Follow tex selected in milo list (only on selection change so combo box choice sticks)
+    let view = &mut state.texture_view;
+    let selected = state.milo_view.selected_object.as_ref();
+
+    if view.followed_object.as_ref() != selected {
+        view.followed_object = selected.cloned();
+
+        if let Some(selected) = selected.filter(|s| tex_names.contains(s)) {
+            if view.tex_name.as_ref().is_none_or(|t| t.ne(selected)) {
+                view.tex_name = Some(selected.to_owned());
+                view.needs_update = true;
+                view.mip = 0;
+            }
+        }
+    }
+
+    egui::ComboBox::from_label("Tex")
+        .selected_text(view.tex_name.as_deref().unwrap_or("(None)"))
+        .show_ui(ui, |ui| {
+            for tex_name in tex_names.iter() {
+                if ui.selectable_label(view.tex_name.as_ref().is_some_and(|t| t.eq(tex_name)), tex_name).clicked() {
+                    view.tex_name = Some(tex_name.to_owned());
+                    view.needs_update = true;
+                    view.mip = 0;
+                }
+            }
+        });
+
+    let tex = view.tex_name
+        .as_ref()
+        .and_then(|name| milo.get_entries().iter().find(|e| e.get_name().eq(name)))
+        .and_then(|e| match e {
+            Object::Tex(tex) => Some(tex),
+            _ => None
+        });
+
+    let Some(tex) = tex else {
+        ui.label("No texture selected");
+        return;
+    };
+
+    let system_info = state.system_info.as_ref().unwrap();
+
+    // Decode once, cached until tex changes
+    if view.needs_update {
+        view.decoded = tex.bitmap
+            .as_ref()
+            .and_then(|b| decode_bitmap(b, system_info));
+
+        view.handle = None;
+        view.needs_update = false;
+    }
+
+    let Some(decoded) = view.decoded.as_ref() else {
+        ui.label("No embedded bitmap (external texture)");
+        return;
+    };
+
+    if decoded.mips.is_empty() {
+        ui.label("Unable to decode bitmap");
+        return;
+    }
+
+    if view.zoom <= 0.0 {
+        view.zoom = 1.0;
+    }
+
+    view.mip = view.mip.min(decoded.mips.len() - 1);
+
+    egui::Grid::new("texture_info").num_columns(2).striped(true).show(ui, |ui| {
+        ui.label("Size");
+        ui.label(format!("{}x{}", tex.width, tex.height));
+        ui.end_row();
+
+        ui.label("Encoding");
+        ui.label(format!("{:?}", decoded.encoding));
+        ui.end_row();
+
+        ui.label("Mip Maps");
+        ui.label(format!("{}", decoded.mips.len() - 1));
+        ui.end_row();
+
+        ui.label("Platform");
+        ui.label(get_platform_name(&system_info.platform));
+        ui.end_row();
+    });
+
+    ui.separator();
+
+    let (prev_mip, prev_channel) = (view.mip, view.channel);
+
+    ui.horizontal(|ui| {
+        ui.label("Mip");
+        ui.add(egui::Slider::new(&mut view.mip, 0..=(decoded.mips.len() - 1)));
+    });
+
+    ui.horizontal(|ui| {
+        for (channel, name) in CHANNELS {
+            ui.selectable_value(&mut view.channel, channel, name);
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Zoom");
+        ui.add(egui::Slider::new(&mut view.zoom, 0.125..=8.0).logarithmic(true));
+
+        if ui.button("1:1").clicked() {
+            view.zoom = 1.0;
+        }
+    });
+
+    let mut file_event = None;
+
+    ui.horizontal(|ui| {
+        if view.image_path.is_empty() {
+            view.image_path = format!("{}.png", tex.get_name().split('.').next().unwrap_or_default());
+        }
+
+        ui.text_edit_singleline(&mut view.image_path);
+
+        if ui.button("Export PNG").clicked() {
+            let tex_name = tex.get_name().to_owned();
+            let png_path = view.image_path.trim().into();
+
+            file_event = Some(AppFileEvent::ExportTexture(tex_name, png_path));
+        }
+
+        if ui.button("Import PNG").clicked() {
+            let tex_name = tex.get_name().to_owned();
+            let png_path = view.image_path.trim().into();
+
+            file_event = Some(AppFileEvent::ImportTexture(tex_name, png_path));
+        }
+    });
+
+    if let Some(ev) = file_event {
+        state.add_file_event(ev);
+    }
+
+    let view = &mut state.texture_view;
+    let decoded = view.decoded.as_ref().unwrap();
+    let mip = &decoded.mips[view.mip];
+
+    if view.handle.is_none() || prev_mip != view.mip || prev_channel != view.channel {
+        let image = egui::ColorImage::from_rgba_unmultiplied(
+            [mip.width as usize, mip.height as usize],
+            &get_channel_pixels(mip, view.channel)
+        );
+
+        view.handle = Some(ctx.load_texture("texture_view", image, egui::TextureOptions::NEAREST));
+    }
+
+    let handle = view.handle.as_ref().unwrap();
+    let size = egui::Vec2::new(mip.width as f32, mip.height as f32) * view.zoom;
+
+    ui.separator();
+
+    egui::ScrollArea::both().id_salt("texture_scroll").show(ui, |ui| {
+        ui.image(egui::load::SizedTexture::new(handle.id(), size));
+    });
+}
+
+fn get_channel_pixels(mip: &DecodedMip, channel: TextureChannel) -> Vec<u8> {
+    let channel_idx = match channel {
+        TextureChannel::RGBA => return mip.rgba.to_owned(),
+        TextureChannel::RGB => {
+            return mip.rgba
+                .chunks_exact(4)
+                .flat_map(|p| [p[0], p[1], p[2], 255])
+                .collect();
+        },
+        TextureChannel::R => 0,
+        TextureChannel::G => 1,
+        TextureChannel::B => 2,
+        TextureChannel::A => 3,
+    };
+
+    // Show single channel as greyscale
+    mip.rgba
+        .chunks_exact(4)
+        .flat_map(|p| [p[channel_idx], p[channel_idx], p[channel_idx], 255])
+        .collect()
+}
@@ -1,9 +1,23 @@
 #![allow(unused_must_use)]
 
 use bevy_egui::{EguiContext, EguiPlugin, egui, egui::{Color32, Context, Pos2, Ui}};
-use super::{AppSettings, AppState, ArkDirNode, AppEvent};
+use super::{AppSettings, AppState, ArkDirNode, AppEvent, AppFileEvent, CloseAction};
+
+const UNDO_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+const REDO_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Y);
+const REDO_ALT_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND.plus(egui::Modifiers::SHIFT), egui::Key::Z);
 
 pub fn render_toolbar(ctx: &mut &Context, settings: &mut AppSettings, state: &mut AppState) {
+    // Let text fields handle their own undo
+    if !ctx.wants_keyboard_input() {
+        // Check shift first, otherwise undo shortcut also matches
+        if ctx.input_mut(|i| i.consume_shortcut(&REDO_ALT_SHORTCUT) || i.consume_shortcut(&REDO_SHORTCUT)) {
+            state.redo();
+        } else if ctx.input_mut(|i| i.consume_shortcut(&UNDO_SHORTCUT)) {
+            state.undo();
+        }
+    }
+
     egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
         // ui.heading("Main");
 
@@ -12,19 +26,45 @@ pub fn render_toolbar(ctx: &mut &Context, settings: &mut AppSettings, state: &mu
             egui::menu::menu_button(ui, "File", |ui| {
                 ui.set_min_width(80.0);
 
-                ui.button("Open");
+                if ui.button("Open").clicked() {
+                    state.file_browser.show(settings.recent_dirs.first());
+                    ui.close_menu();
+                }
+
+                ui.separator();
+
+                if ui.button("Save").clicked() {
+                    state.add_file_event(AppFileEvent::Save);
+                    ui.close_menu();
+                }
+
+                if ui.add_enabled(state.can_save_milo(), egui::Button::new("Save As...")).clicked() {
+                    state.show_save_as();
+                    ui.close_menu();
+                }
                 ui.separator();
 
-                ui.button("Save");
-                ui.button("Save As...");
+                if ui.add_enabled(state.can_save_milo(), egui::Button::new("Import Model...")).clicked() {
+                    state.import_model.open = true;
+                    ui.close_menu();
+                }
+
+                if ui.add_enabled(state.milo.is_some(), egui::Button::new("Export glTF...")).clicked() {
+                    state.show_export_gltf();
+                    ui.close_menu();
+                }
                 ui.separator();
 
-                ui.button("Close");
+                if ui.button("Close").clicked() {
+                    state.request_close(CloseAction::Close);
+                    ui.close_menu();
+                }
                 ui.separator();
 
                 if ui.button("Exit").clicked() {
                     // Close app
-                    state.add_event(AppEvent::Exit);
+                    state.request_close(CloseAction::Exit);
+                    ui.close_menu();
                 }
             });
 
@@ -32,8 +72,13 @@ pub fn render_toolbar(ctx: &mut &Context, settings: &mut AppSettings, state: &mu
             egui::menu::menu_button(ui, "Edit", |ui| {
                 ui.set_min_width(80.0);
 
-                ui.button("Undo");
-                ui.button("Redo");
+                if ui.add_enabled(state.history.can_undo(), egui::Button::new("Undo").shortcut_text(ui.ctx().format_shortcut(&UNDO_SHORTCUT))).clicked() {
+                    state.undo();
+                }
+
+                if ui.add_enabled(state.history.can_redo(), egui::Button::new("Redo").shortcut_text(ui.ctx().format_shortcut(&REDO_SHORTCUT))).clicked() {
+                    state.redo();
+                }
             });
 
             // View dropdown
@@ -43,12 +88,23 @@ pub fn render_toolbar(ctx: &mut &Context, settings: &mut AppSettings, state: &mu
                 if ui.checkbox(&mut settings.show_controls, "Controls").changed() {
                     state.save_settings(&settings);
                 }
+
+                ui.checkbox(&mut state.show_history, "History");
+                ui.checkbox(&mut state.show_references, "References");
             });
 
             // Tools dropdown
             egui::menu::menu_button(ui, "Tools", |ui| {
                 ui.set_min_width(80.0);
 
+                if ui.add_enabled(state.can_save_milo(), egui::Button::new("Verify Round Trip"))
+                    .on_hover_text("Re-save open milo in memory and compare with original")
+                    .clicked() {
+                    state.add_file_event(AppFileEvent::VerifyRoundTrip);
+                    ui.close_menu();
+                }
+                ui.separator();
+
                 if ui.button("Options").clicked() {
                     state.show_options = true;
                 }
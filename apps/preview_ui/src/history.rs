@@ -0,0 +1,322 @@
+use pikaxe::scene::{Color3, Matrix, Object, ObjectDir};
+use pikaxe::texture::Bitmap;
+
+// Commands hold whatever isn't currently in the milo so applying/reverting
+// just moves objects back and forth (no cloning needed)
+
+pub enum PropertyValue {
+    Parent(String),
+    LocalXfm(Matrix),
+    Color(Color3),
+    Alpha(f32),
+    DiffuseTex(String),
+    NormalMap(String),
+    EmissiveMap(String),
+    MeshMat(String),
+    ExtPath(String),
+    GroupObjects(Vec<String>),
+    Bitmap(Option<Bitmap>),
+}
+
+pub enum EditCommand {
+    SetObject(Object), // Replaces object with same name
+    SetProperty { name: String, value: PropertyValue },
+    Rename { old_name: String, new_name: String },
+    Delete { names: Vec<String>, removed: Vec<(usize, Object)> },
+    Import { names: Vec<String>, pending: Vec<Object> },
+}
+
+pub struct EditRecord {
+    pub description: String,
+    command: EditCommand,
+}
+
+pub struct EditHistory {
+    undo_stack: Vec<EditRecord>,
+    redo_stack: Vec<EditRecord>,
+    saved_depth: Option<usize>, // Undo depth at last save, None if unreachable
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        EditHistory {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            saved_depth: Some(0),
+        }
+    }
+}
+
+impl EditCommand {
+    pub fn set_object(obj: Object) -> Self {
+        EditCommand::SetObject(obj)
+    }
+
+    pub fn set_property(name: &str, value: PropertyValue) -> Self {
+        EditCommand::SetProperty {
+            name: name.to_owned(),
+            value,
+        }
+    }
+
+    pub fn rename(old_name: &str, new_name: &str) -> Self {
+        EditCommand::Rename {
+            old_name: old_name.to_owned(),
+            new_name: new_name.to_owned(),
+        }
+    }
+
+    pub fn delete(names: Vec<String>) -> Self {
+        EditCommand::Delete {
+            names,
+            removed: Vec::new(),
+        }
+    }
+
+    pub fn import(objects: Vec<Object>) -> Self {
+        EditCommand::Import {
+            names: objects.iter().map(|o| o.get_name().to_owned()).collect(),
+            pending: objects,
+        }
+    }
+
+    fn apply(&mut self, obj_dir: &mut ObjectDir) -> bool {
+        let entries = get_entries_mut(obj_dir);
+
+        match self {
+            EditCommand::SetObject(obj) => swap_entry(entries, obj),
+            EditCommand::SetProperty { name, value } => swap_property(entries, name, value),
+            EditCommand::Rename { old_name, new_name } => {
+                rename_entry(entries, old_name, new_name)
+            },
+            EditCommand::Delete { names, removed } => {
+                *removed = remove_entries(entries, names);
+                !removed.is_empty()
+            },
+            EditCommand::Import { pending, .. } => {
+                entries.append(pending);
+                true
+            },
+        }
+    }
+
+    fn revert(&mut self, obj_dir: &mut ObjectDir) -> bool {
+        let entries = get_entries_mut(obj_dir);
+
+        match self {
+            // Swap is its own inverse
+            EditCommand::SetObject(obj) => swap_entry(entries, obj),
+            EditCommand::SetProperty { name, value } => swap_property(entries, name, value),
+            EditCommand::Rename { old_name, new_name } => {
+                rename_entry(entries, new_name, old_name)
+            },
+            EditCommand::Delete { removed, .. } => {
+                // Indices are ascending so original order is restored
+                for (i, entry) in removed.drain(..) {
+                    entries.insert(i.min(entries.len()), entry);
+                }
+
+                true
+            },
+            EditCommand::Import { names, pending } => {
+                *pending = remove_entries(entries, names)
+                    .into_iter()
+                    .map(|(_, e)| e)
+                    .collect();
+
+                true
+            },
+        }
+    }
+
+    fn can_merge(&self, other: &EditCommand) -> bool {
+        match (self, other) {
+            (EditCommand::SetProperty { name: n1, value: v1 }, EditCommand::SetProperty { name: n2, value: v2 }) => {
+                n1.eq(n2) && std::mem::discriminant(v1).eq(&std::mem::discriminant(v2))
+            },
+            _ => false
+        }
+    }
+}
+
+impl EditHistory {
+    /// Applies command to milo and adds to history. Merged edits (i.e. dragging
+    /// a value) are combined with last edit if it changed the same property.
+    pub fn execute(&mut self, obj_dir: &mut ObjectDir, description: String, mut command: EditCommand, merge: bool) -> bool {
+        if !command.apply(obj_dir) {
+            log::warn!("Unable to apply edit \"{}\"", &description);
+            return false;
+        }
+
+        // Last edit still holds value from before merged edits
+        let can_merge = merge
+            && self.redo_stack.is_empty()
+            && self.saved_depth.ne(&Some(self.undo_stack.len()))
+            && self.undo_stack.last().is_some_and(|r| r.command.can_merge(&command));
+
+        if can_merge {
+            return true;
+        }
+
+        // Saved state can't be reached through redo anymore
+        if self.saved_depth.is_some_and(|d| d > self.undo_stack.len()) {
+            self.saved_depth = None;
+        }
+
+        log::debug!("Applied edit \"{}\"", &description);
+
+        self.redo_stack.clear();
+        self.undo_stack.push(EditRecord { description, command });
+        true
+    }
+
+    pub fn undo(&mut self, obj_dir: &mut ObjectDir) -> bool {
+        let Some(mut record) = self.undo_stack.pop() else {
+            return false;
+        };
+
+        record.command.revert(obj_dir);
+        log::debug!("Undid edit \"{}\"", &record.description);
+
+        self.redo_stack.push(record);
+        true
+    }
+
+    pub fn redo(&mut self, obj_dir: &mut ObjectDir) -> bool {
+        let Some(mut record) = self.redo_stack.pop() else {
+            return false;
+        };
+
+        record.command.apply(obj_dir);
+        log::debug!("Redid edit \"{}\"", &record.description);
+
+        self.undo_stack.push(record);
+        true
+    }
+
+    /// Undo/redo until history depth matches
+    pub fn jump_to(&mut self, obj_dir: &mut ObjectDir, depth: usize) {
+        while self.undo_stack.len() > depth && self.undo(obj_dir) {}
+        while self.undo_stack.len() < depth && self.redo(obj_dir) {}
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    pub fn get_depth(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    /// Oldest to newest, includes undone edits
+    pub fn get_records(&self) -> impl Iterator<Item = &EditRecord> {
+        self.undo_stack
+            .iter()
+            .chain(self.redo_stack.iter().rev())
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.saved_depth.ne(&Some(self.undo_stack.len()))
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.saved_depth = Some(self.undo_stack.len());
+    }
+}
+
+fn get_entries_mut(obj_dir: &mut ObjectDir) -> &mut Vec<Object> {
+    match obj_dir {
+        ObjectDir::ObjectDir(dir) => &mut dir.entries,
+    }
+}
+
+fn swap_entry(entries: &mut [Object], obj: &mut Object) -> bool {
+    let Some(entry) = entries.iter_mut().find(|e| e.get_name().eq(obj.get_name())) else {
+        return false;
+    };
+
+    std::mem::swap(entry, obj);
+    true
+}
+
+fn swap_property(entries: &mut [Object], name: &str, value: &mut PropertyValue) -> bool {
+    let Some(entry) = entries.iter_mut().find(|e| e.get_name().eq(name)) else {
+        return false;
+    };
+
+    use std::mem::swap;
+
+    match (entry, value) {
+        (Object::Trans(o), PropertyValue::Parent(v)) => swap(&mut o.parent, v),
+        (Object::Mesh(o), PropertyValue::Parent(v)) => swap(&mut o.parent, v),
+        (Object::Group(o), PropertyValue::Parent(v)) => swap(&mut o.parent, v),
+        (Object::Trans(o), PropertyValue::LocalXfm(v)) => swap(&mut o.local_xfm, v),
+        (Object::Mesh(o), PropertyValue::LocalXfm(v)) => swap(&mut o.local_xfm, v),
+        (Object::Group(o), PropertyValue::LocalXfm(v)) => swap(&mut o.local_xfm, v),
+        (Object::Mat(o), PropertyValue::Color(v)) => swap(&mut o.color, v),
+        (Object::Mat(o), PropertyValue::Alpha(v)) => swap(&mut o.alpha, v),
+        (Object::Mat(o), PropertyValue::DiffuseTex(v)) => swap(&mut o.diffuse_tex, v),
+        (Object::Mat(o), PropertyValue::NormalMap(v)) => swap(&mut o.normal_map, v),
+        (Object::Mat(o), PropertyValue::EmissiveMap(v)) => swap(&mut o.emissive_map, v),
+        (Object::Mesh(o), PropertyValue::MeshMat(v)) => swap(&mut o.mat, v),
+        (Object::Tex(o), PropertyValue::ExtPath(v)) => swap(&mut o.ext_path, v),
+        (Object::Group(o), PropertyValue::GroupObjects(v)) => swap(&mut o.objects, v),
+        (Object::Tex(o), PropertyValue::Bitmap(v)) => {
+            swap(&mut o.bitmap, v);
+
+            // Keep tex in sync with bitmap
+            if let Some(bitmap) = o.bitmap.as_ref() {
+                o.width = bitmap.width as _;
+                o.height = bitmap.height as _;
+                o.bpp = bitmap.bpp as _;
+            }
+        },
+        _ => {
+            log::warn!("Property not supported for \"{}\"", name);
+            return false;
+        }
+    }
+
+    true
+}
+
+fn remove_entries(entries: &mut Vec<Object>, names: &[String]) -> Vec<(usize, Object)> {
+    let mut removed = Vec::new();
+    let mut i = 0;
+
+    // Keep original indices (ascending) so entries can be re-inserted
+    while i < entries.len() {
+        if names.iter().any(|n| n.eq(entries[i].get_name())) {
+            removed.push((i + removed.len(), entries.remove(i)));
+        } else {
+            i += 1;
+        }
+    }
+
+    removed
+}
+
+fn rename_entry(entries: &mut [Object], old_name: &str, new_name: &str) -> bool {
+    let Some(entry) = entries.iter_mut().find(|e| e.get_name().eq(old_name)) else {
+        return false;
+    };
+
+    match entry {
+        Object::Group(o) => o.name = new_name.to_owned(),
+        Object::Mat(o) => o.name = new_name.to_owned(),
+        Object::Mesh(o) => o.name = new_name.to_owned(),
+        Object::Tex(o) => o.name = new_name.to_owned(),
+        Object::Trans(o) => o.name = new_name.to_owned(),
+        Object::Packed(o) => o.name = new_name.to_owned(),
+        _ => {
+            log::warn!("Can't rename \"{}\", unsupported type", old_name);
+            return false;
+        }
+    }
+
+    true
+}
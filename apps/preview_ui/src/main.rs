@@ -6,6 +6,7 @@
 
 mod events;
 mod gui;
+mod history;
 mod plugins;
 mod render;
 mod settings;
@@ -13,21 +14,24 @@ mod state;
 
 use events::*;
 use gui::*;
+use history::*;
 use render::{render_milo, render_milo_entry};
 use settings::*;
-use bevy::{prelude::*, render::camera::PerspectiveProjection, window::{PresentMode, PrimaryWindow, WindowMode, WindowResized}, winit::WinitWindows};
+use bevy::{prelude::*, render::camera::PerspectiveProjection, window::{PresentMode, PrimaryWindow, WindowCloseRequested, WindowMode, WindowResized}, winit::WinitWindows};
 use bevy_egui::{EguiContext, EguiContextPass, EguiPlugin, egui, egui::{Color32, Context, Pos2, Ui}};
 use bevy_fly_camera::{FlyCamera, FlyCameraPlugin};
 use bevy_infinite_grid::{InfiniteGrid, InfiniteGridBundle, InfiniteGridPlugin, InfiniteGridSettings};
 use pikaxe::*;
-use pikaxe::ark::{Ark, ArkOffsetEntry};
+use pikaxe::ark::{Ark, ArkBuilder, ArkOffsetEntry};
+use pikaxe::io::BlockType;
 use pikaxe::scene::*;
+use pikaxe_bevy::prelude::{MiloEntitySelected, MiloPickable, MiloPickingPlugin, MiloPickingSettings, MiloWorldSettings};
 use log::{debug, info, warn};
 use plugins::*;
 use state::*;
-use std::{env::args, path::{Path, PathBuf}};
+use std::{collections::HashSet, env::args, path::{Path, PathBuf}};
 
-use crate::render::open_and_unpack_milo;
+use crate::render::{decode_bitmap, encode_bitmap, export_milo_gltf, export_png, GltfExportSettings, import_model, import_png, open_and_unpack_milo, save_milo, save_milo_for_platform, unpack_milo_from_bytes, verify_milo_round_trip};
 
 #[derive(Component)]
 pub struct WorldMesh {
@@ -47,19 +51,24 @@ fn main() {
         })
         .add_plugins(FlyCameraPlugin)
         .add_plugins(InfiniteGridPlugin)
+        .add_plugins(MiloPickingPlugin)
+        .init_resource::<MiloWorldSettings>() // Same conversion as MiloPlugin
         .add_systems(EguiContextPass, render_gui_system)
         .add_systems(Update, detect_meshes)
+        .add_systems(Update, update_mesh_visibility)
         .add_systems(Update, control_camera)
         .add_systems(Update, drop_files)
         .add_systems(Update, window_resized)
+        .add_systems(Update, window_close_requested)
         .add_systems(Update, consume_file_events)
         .add_systems(Update, consume_app_events)
+        .add_systems(Update, consume_selection_events)
         .add_systems(Startup, setup_args)
         .add_systems(Startup, setup)
         .run();
 }
 
-fn render_gui_system(mut settings: ResMut<AppSettings>, mut state: ResMut<AppState>, egui_ctx_query: Single<&mut EguiContext, With<PrimaryWindow>>, mut event_writer: EventWriter<AppEvent>) {
+fn render_gui_system(mut settings: ResMut<AppSettings>, mut state: ResMut<AppState>, egui_ctx_query: Single<&mut EguiContext, With<PrimaryWindow>>, mut event_writer: EventWriter<AppEvent>, mut file_event_writer: EventWriter<AppFileEvent>) {
     let egui_ctx = egui_ctx_query.into_inner();
 
     render_gui(&mut egui_ctx.get(), &mut *settings, &mut *state);
@@ -68,6 +77,10 @@ fn render_gui_system(mut settings: ResMut<AppSettings>, mut state: ResMut<AppSta
     state.consume_events(|ev| {
         event_writer.write(ev);
     });
+
+    state.consume_file_events(|ev| {
+        file_event_writer.write(ev);
+    });
 }
 
 fn detect_meshes(
@@ -87,6 +100,21 @@ fn detect_meshes(
     state.face_count = face_count;
 }
 
+fn update_mesh_visibility(
+    state: Res<AppState>,
+    mut mesh_entities: Query<(&WorldMesh, &mut Visibility)>,
+) {
+    // Hidden from milo scene tree
+    for (world_mesh, mut visibility) in mesh_entities.iter_mut() {
+        let new_visibility = match state.milo_view.hidden_meshes.contains(&world_mesh.name) {
+            true => Visibility::Hidden,
+            _ => Visibility::Inherited,
+        };
+
+        visibility.set_if_neq(new_visibility);
+    }
+}
+
 fn setup(
     mut commands: Commands,
     _meshes: ResMut<Assets<Mesh>>,
@@ -198,6 +226,56 @@ fn consume_file_events(
             AppFileEvent::Open(file_path) => {
                 //milo_event_writer.send(bevy::app::AppExit);
                 open_file(file_path, &mut state, &mut app_event_writer);
+            },
+            AppFileEvent::OpenArkEntry(entry_path) => {
+                open_ark_entry(entry_path, &mut state, &mut app_event_writer);
+            },
+            AppFileEvent::ExtractArkEntries(entry_paths, out_dir) => {
+                extract_ark_entries(entry_paths, out_dir, &state);
+            },
+            AppFileEvent::Save => {
+                if let Some(milo_path) = state.get_milo_file_path().cloned() {
+                    let info = state.system_info.to_owned().unwrap();
+                    let block_type = state.milo_block_type.to_owned().unwrap();
+
+                    save_milo_file(&milo_path, info, block_type, &mut state);
+                } else if state.ark.is_some() && !state.ark_view.staged_files.is_empty() {
+                    save_ark(&mut state);
+                } else if state.can_save_milo() {
+                    // Milo opened from ark
+                    state.show_save_as();
+                } else {
+                    info!("Nothing to save");
+                }
+            },
+            AppFileEvent::SaveAs(milo_path, info, block_type) => {
+                save_milo_file(milo_path, info.to_owned(), block_type.to_owned(), &mut state);
+            },
+            AppFileEvent::VerifyRoundTrip => {
+                verify_round_trip(&state);
+            },
+            AppFileEvent::ExportTexture(tex_name, png_path) => {
+                export_texture(tex_name, png_path, &state);
+            },
+            AppFileEvent::ImportTexture(tex_name, png_path) => {
+                import_texture(tex_name, png_path, &mut state);
+            },
+            AppFileEvent::ExportGltf(glb_path, settings) => {
+                export_gltf(glb_path, settings, &state);
+            },
+            AppFileEvent::ImportModel(model_path) => {
+                import_model_file(model_path, &mut state);
+            }
+        }
+
+        // Continue exit/close from unsaved changes prompt (save as window sends own event when done)
+        let waiting_on_save_as = matches!(e, AppFileEvent::Save) && state.save_as.open;
+
+        if let (AppFileEvent::Save | AppFileEvent::SaveAs(..), Some(action), false) = (e, state.close_after_save, waiting_on_save_as) {
+            state.close_after_save = None;
+
+            if !state.is_dirty() {
+                app_event_writer.write(action.into());
             }
         }
     }
@@ -213,6 +291,7 @@ fn consume_app_events(
     mut textures: ResMut<Assets<Image>>,
     mut grid: Single<&mut Visibility, With<InfiniteGrid>>,
     mut wireframe_config: ResMut<bevy::pbr::wireframe::WireframeConfig>,
+    world_settings: Res<MiloWorldSettings>,
     world_meshes: Query<(Entity, &WorldMesh)>,
 ) {
     for e in app_events.read() {
@@ -220,6 +299,23 @@ fn consume_app_events(
             AppEvent::Exit => {
                 bevy_event_writer.write(bevy::app::AppExit::Success);
             },
+            AppEvent::Close => {
+                for (entity, _) in world_meshes.iter() {
+                    commands.entity(entity).despawn();
+                }
+
+                // Keep app settings + windows
+                *state = AppState {
+                    settings_path: std::mem::take(&mut state.settings_path),
+                    show_options: state.show_options,
+                    show_history: state.show_history,
+                    show_references: state.show_references,
+                    file_browser: std::mem::take(&mut state.file_browser),
+                    ..Default::default()
+                };
+
+                info!("Closed file");
+            },
             AppEvent::SelectMiloEntry(entry_name) => {
                 /*let render_entry = match &state.milo_view.selected_entry {
                     Some(name) => name.ne(entry_name),
@@ -252,7 +348,12 @@ fn consume_app_events(
                 }*/
 
                 let milo = state.milo.as_ref().unwrap();
-                let milo_path = state.open_file_path.as_ref().unwrap();
+                // Ark entries don't exist on disk but path is still needed for ext textures
+                let milo_path = state.open_ark_entry
+                    .as_ref()
+                    .map(PathBuf::from)
+                    .or_else(|| state.open_file_path.to_owned())
+                    .unwrap();
                 let info = state.system_info.as_ref().unwrap();
 
                 // Render everything for now
@@ -262,9 +363,10 @@ fn consume_app_events(
                     &mut materials,
                     &mut textures,
                     milo,
-                    milo_path,
+                    &milo_path,
                     entry_name.to_owned(),
-                    info
+                    info,
+                    &world_settings
                 );
 
                 state.milo_view.selected_entry = entry_name.to_owned();
@@ -303,6 +405,21 @@ fn consume_app_events(
     }
 }
 
+fn consume_selection_events(
+    mut selected_events: EventReader<MiloEntitySelected>,
+    mut state: ResMut<AppState>,
+    world_meshes: Query<&WorldMesh>,
+) {
+    for MiloEntitySelected(entity) in selected_events.read() {
+        // Sync with milo list
+        state.milo_view.selected_object = entity
+            .and_then(|en| world_meshes.get(en).ok())
+            .map(|wm| wm.name.to_owned());
+
+        state.milo_view.scroll_to_selected = true;
+    }
+}
+
 fn open_file(
     file_path: &PathBuf,
     state: &mut ResMut<AppState>,
@@ -310,19 +427,14 @@ fn open_file(
 ) {
     // Clear file path
     state.open_file_path.take();
+    state.open_ark_entry.take();
 
-    // Get full file extension
-    let ext = file_path
+    let file_type = file_path
         .file_name()
         .and_then(|n| n.to_str())
-        .map(|n| match n.find('.') {
-            Some(i) => &n[i..],
-            _ => n
-        })
-        .unwrap();
+        .and_then(get_file_type);
 
-    // TODO: Make case-insensitive
-    if ext.contains("hdr") {
+    if file_type == Some(FileType::Ark) {
         // Open ark
         info!("Opening hdr from \"{}\"", file_path.display());
 
@@ -333,20 +445,24 @@ fn open_file(
             state.root = Some(create_ark_tree(&ark));
             state.ark = Some(ark);
             state.open_file_path = Some(file_path.to_owned());
+            state.ark_view = ArkView::default();
         }
-    } else if ext.contains("milo")
-        || ext.contains("gh")
-        || ext.contains("rnd") { // TODO: Break out into static regex
+    } else if file_type == Some(FileType::Milo) {
         // Open milo
         info!("Opening milo from \"{}\"", file_path.display());
 
         match open_and_unpack_milo(file_path) {
-            Ok((milo, info)) => {
+            Ok((milo, info, block_type)) => {
                 debug!("Successfully opened milo with {} entries", milo.get_entries().len());
 
                 state.milo = Some(milo);
                 state.system_info = Some(info);
+                state.milo_block_type = Some(block_type);
                 state.open_file_path = Some(file_path.to_owned());
+                state.history = EditHistory::default();
+                state.texture_view = TextureView::default();
+                state.milo_view.hidden_objects.clear();
+                state.milo_view.hidden_meshes.clear();
 
                 //ev_update_state.send(AppEvent::RefreshMilo);
 
@@ -383,6 +499,370 @@ fn open_file(
     }
 }
 
+fn open_ark_entry(
+    entry_path: &str,
+    state: &mut ResMut<AppState>,
+    app_event_writer: &mut EventWriter<AppEvent>,
+) {
+    let Some(ark) = state.ark.as_ref() else {
+        warn!("Can't open \"{}\", no ark loaded", entry_path);
+        return;
+    };
+
+    let Some(entry) = ark.entries.iter().find(|e| e.path.eq(entry_path)) else {
+        warn!("Can't find \"{}\" in ark", entry_path);
+        return;
+    };
+
+    info!("Opening milo from ark entry \"{}\"", entry_path);
+
+    let data = match ark.get_stream(entry.id) {
+        Ok(data) => data,
+        Err(err) => {
+            warn!("Unable to read ark entry:\n\t: {:?}", err);
+            return;
+        }
+    };
+
+    match unpack_milo_from_bytes(&data, entry_path) {
+        Ok((milo, info, block_type)) => {
+            debug!("Successfully opened milo with {} entries", milo.get_entries().len());
+
+            state.milo = Some(milo);
+            state.system_info = Some(info);
+            state.milo_block_type = Some(block_type);
+            state.open_ark_entry = Some(entry_path.to_owned());
+            state.history = EditHistory::default();
+            state.texture_view = TextureView::default();
+            state.milo_view.hidden_objects.clear();
+            state.milo_view.hidden_meshes.clear();
+
+            // Switch to milo tab
+            state.side_bar_tab = SideBarTab::Milo;
+            state.milo_view.selected_object = None;
+
+            app_event_writer.write(AppEvent::SelectMiloEntry(None));
+        },
+        Err(err) => {
+            warn!("Unable to unpack milo file:\n\t: {:?}", err);
+        }
+    }
+}
+
+fn save_milo_file(
+    milo_path: &Path,
+    info: SystemInfo,
+    block_type: BlockType,
+    state: &mut ResMut<AppState>,
+) {
+    let Some(current_info) = state.system_info.to_owned() else {
+        return;
+    };
+
+    let Some(milo) = state.milo.as_mut() else {
+        return;
+    };
+
+    let converted = current_info.platform.ne(&info.platform);
+
+    let res = match converted {
+        true => {
+            info!("Saving milo to \"{}\" (converting platform)", milo_path.display());
+            save_milo_for_platform(milo_path, milo, &current_info, &info, block_type)
+        },
+        _ => {
+            info!("Saving milo to \"{}\"", milo_path.display());
+            save_milo(milo_path, milo, &info, block_type)
+        }
+    };
+
+    if let Err(err) = res {
+        warn!("Unable to save milo file:\n\t: {:?}", err);
+        return;
+    }
+
+    info!("Saved milo to \"{}\"", milo_path.display());
+    state.history.mark_saved();
+
+    // Saved milo is now the open file (open milo stays on original platform if converted)
+    if state.get_milo_file_path().is_some() && !converted {
+        state.open_file_path = Some(milo_path.to_owned());
+        state.system_info = Some(info);
+        state.milo_block_type = Some(block_type);
+    }
+}
+
+fn verify_round_trip(
+    state: &ResMut<AppState>,
+) {
+    // Read original bytes from ark or disk
+    let (milo_path, data) = match (&state.open_ark_entry, &state.ark, &state.open_file_path) {
+        (Some(entry_path), Some(ark), _) => {
+            let data = ark.entries
+                .iter()
+                .find(|e| e.path.eq(entry_path))
+                .ok_or_else(|| format!("Can't find \"{entry_path}\" in ark"))
+                .and_then(|e| ark.get_stream(e.id).map_err(|err| format!("{err:?}")));
+
+            (PathBuf::from(entry_path), data)
+        },
+        (None, _, Some(file_path)) if state.get_milo_file_path().is_some() => {
+            let data = std::fs::read(file_path).map_err(|err| err.to_string());
+            (file_path.to_owned(), data)
+        },
+        _ => {
+            info!("No milo open to verify");
+            return;
+        }
+    };
+
+    let data = match data {
+        Ok(data) => data,
+        Err(err) => {
+            warn!("Unable to read milo:\n\t: {}", err);
+            return;
+        }
+    };
+
+    match verify_milo_round_trip(&data, &milo_path) {
+        Ok(None) => info!("Round trip OK, \"{}\" saves to identical bytes ({} bytes)", milo_path.display(), data.len()),
+        Ok(Some(offset)) => warn!("Round trip mismatch for \"{}\" at offset 0x{:X}", milo_path.display(), offset),
+        Err(err) => warn!("Unable to verify milo:\n\t: {:?}", err),
+    }
+}
+
+fn export_texture(
+    tex_name: &str,
+    png_path: &Path,
+    state: &ResMut<AppState>,
+) {
+    let (Some(milo), Some(info)) = (state.milo.as_ref(), state.system_info.as_ref()) else {
+        return;
+    };
+
+    let bitmap = milo
+        .get_entries()
+        .iter()
+        .find_map(|e| match e {
+            Object::Tex(tex) if tex.get_name().eq(tex_name) => tex.bitmap.as_ref(),
+            _ => None
+        });
+
+    let Some(decoded) = bitmap.and_then(|b| decode_bitmap(b, info)) else {
+        warn!("Unable to decode bitmap for \"{}\"", tex_name);
+        return;
+    };
+
+    // Only base image is exported
+    match decoded.mips.first().map(|mip| export_png(png_path, mip)) {
+        Some(Ok(_)) => info!("Exported \"{}\" to \"{}\"", tex_name, png_path.display()),
+        Some(Err(err)) => warn!("Unable to export texture:\n\t: {:?}", err),
+        None => warn!("No image data for \"{}\"", tex_name),
+    }
+}
+
+fn import_texture(
+    tex_name: &str,
+    png_path: &Path,
+    state: &mut ResMut<AppState>,
+) {
+    let (Some(milo), Some(info)) = (state.milo.as_ref(), state.system_info.as_ref()) else {
+        return;
+    };
+
+    let template = milo
+        .get_entries()
+        .iter()
+        .find_map(|e| match e {
+            Object::Tex(tex) if tex.get_name().eq(tex_name) => tex.bitmap.as_ref(),
+            _ => None
+        });
+
+    let Some(template) = template else {
+        warn!("\"{}\" has no embedded bitmap to replace", tex_name);
+        return;
+    };
+
+    let image = match import_png(png_path) {
+        Ok(image) => image,
+        Err(err) => {
+            warn!("Unable to open image:\n\t: {:?}", err);
+            return;
+        }
+    };
+
+    let bitmap = encode_bitmap(&image, template, info);
+    info!("Encoded \"{}\" as {}x{} ({} mips)", png_path.display(), bitmap.width, bitmap.height, bitmap.mip_maps);
+
+    state.execute_edit(
+        format!("Import image to \"{tex_name}\""),
+        EditCommand::set_property(tex_name, PropertyValue::Bitmap(Some(bitmap)))
+    );
+}
+
+fn export_gltf(
+    glb_path: &Path,
+    settings: &GltfExportSettings,
+    state: &ResMut<AppState>,
+) {
+    let (Some(milo), Some(info)) = (state.milo.as_ref(), state.system_info.as_ref()) else {
+        return;
+    };
+
+    // Name of root node
+    let milo_path = state.open_ark_entry
+        .as_ref()
+        .map(PathBuf::from)
+        .or_else(|| state.open_file_path.to_owned())
+        .unwrap_or_default();
+
+    match export_milo_gltf(milo, &milo_path, info, glb_path, settings) {
+        Ok(_) => info!("Exported glTF to \"{}\"", glb_path.display()),
+        Err(err) => warn!("Unable to export glTF:\n\t: {:?}", err),
+    }
+}
+
+fn import_model_file(
+    model_path: &Path,
+    state: &mut ResMut<AppState>,
+) {
+    let (Some(milo), Some(info)) = (state.milo.as_ref(), state.system_info.as_ref()) else {
+        return;
+    };
+
+    let names = milo
+        .get_entries()
+        .iter()
+        .map(|e| e.get_name().as_str())
+        .collect::<Vec<_>>();
+
+    let objects = match import_model(model_path, info, &names) {
+        Ok(objects) => objects,
+        Err(err) => {
+            warn!("Unable to import model:\n\t: {:?}", err);
+            return;
+        }
+    };
+
+    info!("Imported {} objects from \"{}\"", objects.len(), model_path.display());
+
+    // Group is always last, show it once added
+    state.milo_view.selected_entry = objects
+        .last()
+        .map(|o| o.get_name().to_owned());
+
+    let file_name = model_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    state.execute_edit(format!("Import \"{file_name}\""), EditCommand::import(objects));
+}
+
+fn extract_ark_entries(
+    entry_paths: &[String],
+    out_dir: &Path,
+    state: &ResMut<AppState>,
+) {
+    let Some(ark) = state.ark.as_ref() else {
+        return;
+    };
+
+    let entry_paths = entry_paths
+        .iter()
+        .map(|p| p.as_str())
+        .collect::<HashSet<_>>();
+
+    let mut count = 0;
+
+    for entry in ark.entries.iter().filter(|e| entry_paths.contains(e.path.as_str())) {
+        let out_path = out_dir.join(entry.path.trim_start_matches("./"));
+
+        let res = ark
+            .get_stream(entry.id)
+            .map_err(|e| format!("{e:?}"))
+            .and_then(|data| {
+                if let Some(dir) = out_path.parent() {
+                    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+                }
+
+                std::fs::write(&out_path, data).map_err(|e| e.to_string())
+            });
+
+        match res {
+            Ok(_) => count += 1,
+            Err(err) => warn!("Unable to extract \"{}\":\n\t: {}", &entry.path, err)
+        }
+    }
+
+    info!("Extracted {} of {} files to \"{}\"", count, entry_paths.len(), out_dir.display());
+}
+
+fn save_ark(
+    state: &mut ResMut<AppState>,
+) {
+    let hdr_path = state.open_file_path.to_owned().unwrap();
+    let ark = state.ark.as_ref().unwrap();
+
+    info!("Saving ark to \"{}\"", hdr_path.display());
+
+    let mut builder = ArkBuilder::from_ark(ark);
+
+    for (ark_path, local_path) in state.ark_view.staged_files.iter() {
+        match std::fs::read(local_path) {
+            Ok(data) => builder.set_file(ark_path, data),
+            Err(err) => {
+                warn!("Unable to read \"{}\", save cancelled:\n\t: {:?}", local_path.display(), err);
+                return;
+            }
+        }
+    }
+
+    // Builder reads from original ark files, so write to temp dir first and then replace
+    let hdr_dir = hdr_path.parent().unwrap_or(Path::new(".")).to_owned();
+    let temp_dir = hdr_dir.join(".ark_save");
+    let temp_hdr_path = temp_dir.join(hdr_path.file_name().unwrap());
+
+    let res = std::fs::create_dir_all(&temp_dir)
+        .map_err(|e| format!("{e:?}"))
+        .and_then(|_| builder.write_to_path(&temp_hdr_path).map_err(|e| format!("{e:?}")));
+
+    if let Err(err) = res {
+        warn!("Unable to save ark:\n\t: {}", err);
+        std::fs::remove_dir_all(&temp_dir).ok();
+        return;
+    }
+
+    if let Err(err) = replace_files_from_dir(&temp_dir, &hdr_dir) {
+        warn!("Unable to replace ark files, saved files left in \"{}\":\n\t: {:?}", temp_dir.display(), err);
+        return;
+    }
+
+    info!("Saved {} files to ark", state.ark_view.staged_files.len());
+    state.ark_view.staged_files.clear();
+
+    // Reload to get new offsets
+    match Ark::from_path(&hdr_path) {
+        Ok(ark) => {
+            state.root = Some(create_ark_tree(&ark));
+            state.ark = Some(ark);
+        },
+        Err(err) => {
+            warn!("Unable to reload ark:\n\t: {:?}", err);
+        }
+    }
+}
+
+/// Moves files from src dir into dest dir (overwriting) and removes src dir
+fn replace_files_from_dir(src_dir: &Path, dest_dir: &Path) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(src_dir)? {
+        let entry = entry?;
+        std::fs::rename(entry.path(), dest_dir.join(entry.file_name()))?;
+    }
+
+    std::fs::remove_dir(src_dir)
+}
+
 fn create_ark_tree(ark: &Ark) -> ArkDirNode {
     let mut root = ArkDirNode {
         name: ark.path
@@ -406,10 +886,14 @@ fn control_camera(
     mouse_input: Res<ButtonInput<MouseButton>>,
     egui_ctx_query: Single<&mut EguiContext, With<PrimaryWindow>>,
     mut cam_query: Query<&mut FlyCamera>,
+    mut picking_settings: ResMut<MiloPickingSettings>,
 ) {
     let mut egui_ctx = egui_ctx_query.into_inner();
     let ctx = egui_ctx.get_mut();
 
+    // Don't pick through gui
+    picking_settings.enabled = !ctx.wants_pointer_input() && !ctx.is_pointer_over_area();
+
     let key_down = is_camera_button_down(&key_input);
     let mouse_down = mouse_input.pressed(MouseButton::Left);
 
@@ -478,6 +962,16 @@ fn window_resized(
     }
 }
 
+fn window_close_requested(
+    mut close_events: EventReader<WindowCloseRequested>,
+    mut state: ResMut<AppState>,
+) {
+    // Prompt for unsaved changes before exiting
+    if close_events.read().last().is_some() {
+        state.request_close(CloseAction::Exit);
+    }
+}
+
 fn drop_files(
     mut drag_drop_events: EventReader<FileDragAndDrop>,
     mut file_event_writer: EventWriter<AppFileEvent>,
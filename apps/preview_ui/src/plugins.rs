@@ -34,6 +34,7 @@ impl Plugin for GrimPlugin {
                         resizable: true,
                         ..Default::default()
                     }),
+                    close_when_requested: false, // Handled by unsaved changes prompt
                     ..Default::default()
                 }),
                 bevy::pbr::wireframe::WireframePlugin::default(),
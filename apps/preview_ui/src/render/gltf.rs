@@ -0,0 +1,493 @@
+use bevy::prelude::*;
+use log::warn;
+use serde_json::{json, Value};
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::path::Path;
+
+use pikaxe::SystemInfo;
+use pikaxe::scene::{MatObject, MeshObject, MiloObject, Object, ObjectDir, Trans};
+
+use super::milo_entry::get_computed_mat;
+use super::{decode_bitmap, encode_png, map_matrix, MiloLoader};
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_CHUNK_JSON: u32 = 0x4E4F534A;
+const GLB_CHUNK_BIN: u32 = 0x004E4942;
+
+const COMPONENT_U16: u32 = 5123;
+const COMPONENT_F32: u32 = 5126;
+
+const TARGET_ARRAY_BUFFER: u32 = 34962;
+const TARGET_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+// Milo is z-up, gltf is y-up
+const Z_UP_TO_Y_UP: [f32; 16] = [
+    1.0, 0.0,  0.0, 0.0,
+    0.0, 0.0, -1.0, 0.0,
+    0.0, 1.0,  0.0, 0.0,
+    0.0, 0.0,  0.0, 1.0,
+];
+
+#[derive(Clone, Default)]
+pub struct GltfExportSettings {
+    pub selected_entry: Option<String>, // Exports whole milo if none
+    pub embed_textures: bool, // Otherwise written as png next to glb
+}
+
+#[derive(Default)]
+struct GltfBuilder {
+    buffer: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+    images: Vec<Value>,
+    textures: Vec<Value>,
+    materials: Vec<Value>,
+    meshes: Vec<Value>,
+    nodes: Vec<Value>,
+    skins: Vec<Value>,
+    material_indices: HashMap<String, usize>,
+    texture_indices: HashMap<String, Option<usize>>,
+}
+
+/// Writes selected entry (or whole milo) as binary gltf
+pub fn export_milo_gltf<T: AsRef<Path>>(milo: &ObjectDir, milo_path: &Path, system_info: &SystemInfo, glb_path: T, settings: &GltfExportSettings) -> Result<(), Box<dyn Error>> {
+    let glb_path = glb_path.as_ref();
+    let mut loader = MiloLoader::new(milo, milo_path);
+
+    let names = match settings.selected_entry.as_ref() {
+        Some(entry) => {
+            let mut names = Vec::new();
+            collect_object_names(entry, &loader, &mut names, &mut HashSet::new());
+            names
+        },
+        None => milo.get_entries()
+            .iter()
+            .filter(|e| loader.get_transform(e.get_name()).is_some())
+            .map(|e| e.get_name().as_str())
+            .collect()
+    };
+
+    if names.is_empty() {
+        return Err(format!("Nothing to export in \"{}\"", milo_path.display()).into());
+    }
+
+    let mut builder = GltfBuilder::default();
+    let node_indices = names
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (*n, i))
+        .collect::<HashMap<_, _>>();
+
+    let mut root_children = Vec::new();
+    let mut children = vec![Vec::new(); names.len()];
+
+    // Nodes are added in same order as names so indices match
+    for (i, name) in names.iter().enumerate() {
+        let trans = loader.get_transform(name).unwrap();
+        let parent_name = trans.get_parent();
+
+        let parent_idx = node_indices
+            .get(parent_name.as_str())
+            .filter(|_| parent_name.ne(name));
+
+        let matrix = match parent_idx {
+            Some(parent_idx) => {
+                children[*parent_idx].push(i);
+                map_matrix(trans.get_local_xfm())
+            },
+            None => {
+                // Parent is outside of export, bake world transform
+                root_children.push(i);
+                get_computed_mat(trans, &mut loader)
+            }
+        };
+
+        builder.nodes.push(json!({
+            "name": name,
+            "matrix": matrix.to_cols_array(),
+        }));
+    }
+
+    for (i, name) in names.iter().enumerate() {
+        if !children[i].is_empty() {
+            builder.nodes[i]["children"] = json!(children[i]);
+        }
+
+        let Some(mesh) = loader.get_mesh(name) else {
+            continue;
+        };
+
+        // Ignore meshes without geometry
+        if mesh.vertices.is_empty() || mesh.faces.is_empty() {
+            continue;
+        }
+
+        let mat = loader.get_mat(&mesh.mat);
+        let mesh_idx = builder.add_mesh(mesh, mat, &loader, system_info, glb_path, settings.embed_textures);
+        builder.nodes[i]["mesh"] = json!(mesh_idx);
+
+        if let Some(skin_idx) = builder.add_skin(mesh, &node_indices) {
+            builder.nodes[i]["skin"] = json!(skin_idx);
+        }
+    }
+
+    // Root node for coordinate conversion
+    let root_name = milo_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let root_idx = builder.nodes.len();
+    builder.nodes.push(json!({
+        "name": root_name,
+        "matrix": Z_UP_TO_Y_UP,
+        "children": root_children,
+    }));
+
+    let glb = builder.build_glb(root_idx)?;
+    std::fs::write(glb_path, glb)?;
+
+    Ok(())
+}
+
+fn collect_object_names<'a>(name: &str, loader: &MiloLoader<'a>, names: &mut Vec<&'a str>, visited: &mut HashSet<&'a str>) {
+    let Some(obj) = loader.get_object(name) else {
+        return;
+    };
+
+    if !visited.insert(obj.get_name().as_str()) {
+        return;
+    }
+
+    let sub_names = match obj {
+        Object::Group(grp) => grp.objects.as_slice(),
+        Object::Mesh(mesh) => mesh.draw_objects.as_slice(),
+        _ => &[],
+    };
+
+    if loader.get_transform(name).is_some() {
+        names.push(obj.get_name().as_str());
+    }
+
+    for sub_name in sub_names.iter() {
+        collect_object_names(sub_name, loader, names, visited);
+    }
+}
+
+impl GltfBuilder {
+    fn add_buffer_view(&mut self, data: &[u8], target: Option<u32>) -> usize {
+        // Accessors need 4-byte alignment
+        while self.buffer.len() % 4 != 0 {
+            self.buffer.push(0);
+        }
+
+        let mut view = json!({
+            "buffer": 0,
+            "byteOffset": self.buffer.len(),
+            "byteLength": data.len(),
+        });
+
+        if let Some(target) = target {
+            view["target"] = json!(target);
+        }
+
+        self.buffer.extend_from_slice(data);
+        self.buffer_views.push(view);
+        self.buffer_views.len() - 1
+    }
+
+    fn add_f32_accessor<const N: usize>(&mut self, values: &[[f32; N]], accessor_type: &str, target: Option<u32>, with_bounds: bool) -> usize {
+        let data = values
+            .iter()
+            .flat_map(|v| v.iter().flat_map(|f| f.to_le_bytes()))
+            .collect::<Vec<_>>();
+
+        let view_idx = self.add_buffer_view(&data, target);
+
+        let mut accessor = json!({
+            "bufferView": view_idx,
+            "componentType": COMPONENT_F32,
+            "count": values.len(),
+            "type": accessor_type,
+        });
+
+        if with_bounds {
+            // Required for positions
+            let (mut min, mut max) = ([f32::MAX; N], [f32::MIN; N]);
+
+            for v in values.iter() {
+                for i in 0..N {
+                    min[i] = min[i].min(v[i]);
+                    max[i] = max[i].max(v[i]);
+                }
+            }
+
+            accessor["min"] = json!(min.to_vec());
+            accessor["max"] = json!(max.to_vec());
+        }
+
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn add_u16_accessor<const N: usize>(&mut self, values: &[[u16; N]], accessor_type: &str, target: Option<u32>) -> usize {
+        let data = values
+            .iter()
+            .flat_map(|v| v.iter().flat_map(|i| i.to_le_bytes()))
+            .collect::<Vec<_>>();
+
+        let view_idx = self.add_buffer_view(&data, target);
+
+        self.accessors.push(json!({
+            "bufferView": view_idx,
+            "componentType": COMPONENT_U16,
+            "count": values.len() * if accessor_type.eq("SCALAR") { N } else { 1 },
+            "type": accessor_type,
+        }));
+        self.accessors.len() - 1
+    }
+
+    fn add_mesh(&mut self, mesh: &MeshObject, mat: Option<&MatObject>, loader: &MiloLoader, system_info: &SystemInfo, glb_path: &Path, embed_textures: bool) -> usize {
+        let verts = &mesh.vertices;
+
+        let positions = verts
+            .iter()
+            .map(|v| [v.pos.x, v.pos.y, v.pos.z])
+            .collect::<Vec<_>>();
+
+        let normals = verts
+            .iter()
+            .map(|v| {
+                // Gltf requires unit length normals
+                let n = Vec3::new(v.normals.x, v.normals.y, v.normals.z).try_normalize().unwrap_or(Vec3::Z);
+                [n.x, n.y, n.z]
+            })
+            .collect::<Vec<_>>();
+
+        let uvs = verts
+            .iter()
+            .map(|v| [v.uv.u, v.uv.v])
+            .collect::<Vec<_>>();
+
+        let mut attributes = json!({
+            "POSITION": self.add_f32_accessor(&positions, "VEC3", Some(TARGET_ARRAY_BUFFER), true),
+            "NORMAL": self.add_f32_accessor(&normals, "VEC3", Some(TARGET_ARRAY_BUFFER), false),
+            "TEXCOORD_0": self.add_f32_accessor(&uvs, "VEC2", Some(TARGET_ARRAY_BUFFER), false),
+        });
+
+        if !mesh.bones.is_empty() {
+            let joints = verts
+                .iter()
+                .map(|v| v.bones.map(|b| b.min((mesh.bones.len() - 1) as u16)))
+                .collect::<Vec<_>>();
+
+            let weights = verts
+                .iter()
+                .map(|v| {
+                    // Weights need to add up to 1
+                    let sum = v.weights.iter().sum::<f32>();
+
+                    match sum > 0.0 {
+                        true => v.weights.map(|w| w / sum),
+                        _ => [1.0, 0.0, 0.0, 0.0]
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            attributes["JOINTS_0"] = json!(self.add_u16_accessor(&joints, "VEC4", Some(TARGET_ARRAY_BUFFER)));
+            attributes["WEIGHTS_0"] = json!(self.add_f32_accessor(&weights, "VEC4", Some(TARGET_ARRAY_BUFFER), false));
+        }
+
+        let indices = self.add_u16_accessor(&mesh.faces, "SCALAR", Some(TARGET_ELEMENT_ARRAY_BUFFER));
+
+        let mut primitive = json!({
+            "attributes": attributes,
+            "indices": indices,
+        });
+
+        if let Some(mat) = mat {
+            let mat_idx = self.get_or_add_material(mat, loader, system_info, glb_path, embed_textures);
+            primitive["material"] = json!(mat_idx);
+        }
+
+        self.meshes.push(json!({
+            "name": mesh.get_name(),
+            "primitives": [ primitive ],
+        }));
+        self.meshes.len() - 1
+    }
+
+    fn add_skin(&mut self, mesh: &MeshObject, node_indices: &HashMap<&str, usize>) -> Option<usize> {
+        if mesh.bones.is_empty() {
+            return None;
+        }
+
+        let joints = mesh.bones
+            .iter()
+            .map(|b| node_indices.get(b.name.as_str()).copied())
+            .collect::<Option<Vec<_>>>();
+
+        let Some(joints) = joints else {
+            warn!("Skipping skin for \"{}\", bones not included in export", mesh.get_name());
+            return None;
+        };
+
+        let inverse_binds = mesh.bones
+            .iter()
+            .map(|b| map_matrix(&b.trans).to_cols_array())
+            .collect::<Vec<_>>();
+
+        let inverse_binds_idx = self.add_f32_accessor(&inverse_binds, "MAT4", None, false);
+
+        self.skins.push(json!({
+            "name": mesh.get_name(),
+            "joints": joints,
+            "inverseBindMatrices": inverse_binds_idx,
+        }));
+        Some(self.skins.len() - 1)
+    }
+
+    fn get_or_add_material(&mut self, mat: &MatObject, loader: &MiloLoader, system_info: &SystemInfo, glb_path: &Path, embed_textures: bool) -> usize {
+        if let Some(idx) = self.material_indices.get(mat.get_name()) {
+            return *idx;
+        }
+
+        let mut material = json!({
+            "name": mat.get_name(),
+            "pbrMetallicRoughness": {
+                "baseColorFactor": [mat.color.r, mat.color.g, mat.color.b, mat.alpha],
+                "metallicFactor": 0.0,
+                "roughnessFactor": 1.0,
+            },
+            "doubleSided": true,
+        });
+
+        if mat.alpha < 1.0 {
+            material["alphaMode"] = json!("BLEND");
+        }
+
+        if let Some(idx) = self.get_or_add_texture(&mat.diffuse_tex, loader, system_info, glb_path, embed_textures) {
+            material["pbrMetallicRoughness"]["baseColorTexture"] = json!({ "index": idx });
+        }
+
+        if let Some(idx) = self.get_or_add_texture(&mat.normal_map, loader, system_info, glb_path, embed_textures) {
+            material["normalTexture"] = json!({ "index": idx });
+        }
+
+        if let Some(idx) = self.get_or_add_texture(&mat.emissive_map, loader, system_info, glb_path, embed_textures) {
+            material["emissiveTexture"] = json!({ "index": idx });
+            material["emissiveFactor"] = json!([1.0, 1.0, 1.0]);
+        }
+
+        self.materials.push(material);
+        self.material_indices.insert(mat.get_name().to_owned(), self.materials.len() - 1);
+        self.materials.len() - 1
+    }
+
+    fn get_or_add_texture(&mut self, tex_name: &str, loader: &MiloLoader, system_info: &SystemInfo, glb_path: &Path, embed_textures: bool) -> Option<usize> {
+        if tex_name.is_empty() {
+            return None;
+        }
+
+        if let Some(idx) = self.texture_indices.get(tex_name) {
+            return *idx;
+        }
+
+        // Only embedded bitmaps are supported
+        let png = loader.get_texture(tex_name)
+            .and_then(|t| t.bitmap.as_ref())
+            .and_then(|b| decode_bitmap(b, system_info))
+            .and_then(|d| d.mips.first().and_then(|mip| encode_png(mip).ok()));
+
+        let Some(png) = png else {
+            warn!("Unable to export texture \"{tex_name}\"");
+            self.texture_indices.insert(tex_name.to_owned(), None);
+            return None;
+        };
+
+        let image = match embed_textures {
+            true => {
+                let view_idx = self.add_buffer_view(&png, None);
+
+                json!({
+                    "name": tex_name,
+                    "bufferView": view_idx,
+                    "mimeType": "image/png",
+                })
+            },
+            _ => {
+                let file_name = format!("{}.png", tex_name.split('.').next().unwrap_or(tex_name));
+                let png_path = glb_path.with_file_name(&file_name);
+
+                if let Err(err) = std::fs::write(&png_path, &png) {
+                    warn!("Unable to write \"{}\": {:?}", png_path.display(), err);
+                }
+
+                json!({
+                    "name": tex_name,
+                    "uri": file_name,
+                })
+            }
+        };
+
+        self.images.push(image);
+        self.textures.push(json!({ "source": self.images.len() - 1 }));
+
+        let idx = self.textures.len() - 1;
+        self.texture_indices.insert(tex_name.to_owned(), Some(idx));
+        Some(idx)
+    }
+
+    fn build_glb(mut self, root_idx: usize) -> Result<Vec<u8>, Box<dyn Error>> {
+        while self.buffer.len() % 4 != 0 {
+            self.buffer.push(0);
+        }
+
+        let mut root = json!({
+            "asset": { "version": "2.0", "generator": "preview_ui" },
+            "scene": 0,
+            "scenes": [ { "nodes": [ root_idx ] } ],
+            "nodes": self.nodes,
+            "buffers": [ { "byteLength": self.buffer.len() } ],
+            "bufferViews": self.buffer_views,
+            "accessors": self.accessors,
+        });
+
+        // Empty arrays aren't allowed
+        for (key, values) in [
+            ("meshes", self.meshes),
+            ("materials", self.materials),
+            ("textures", self.textures),
+            ("images", self.images),
+            ("skins", self.skins),
+        ] {
+            if !values.is_empty() {
+                root[key] = Value::Array(values);
+            }
+        }
+
+        let mut json_data = serde_json::to_vec(&root)?;
+
+        while json_data.len() % 4 != 0 {
+            json_data.push(b' ');
+        }
+
+        let total_size = 12 + (8 + json_data.len()) + (8 + self.buffer.len());
+        let mut glb = Vec::with_capacity(total_size);
+
+        // Header
+        glb.extend_from_slice(GLB_MAGIC);
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&(total_size as u32).to_le_bytes());
+
+        for (chunk_type, data) in [(GLB_CHUNK_JSON, &json_data), (GLB_CHUNK_BIN, &self.buffer)] {
+            glb.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            glb.extend_from_slice(&chunk_type.to_le_bytes());
+            glb.extend_from_slice(data);
+        }
+
+        Ok(glb)
+    }
+}
@@ -18,6 +18,7 @@ pub struct MiloLoader<'a> {
     transforms: HashMap<&'a str, &'a dyn Trans>,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum TextureEncoding {
     RGBA,
     DXT1,
@@ -18,6 +18,7 @@ use pikaxe::scene::{GroupObject, Matrix, MeshObject, Milo, MiloObject, Object, O
 use pikaxe::texture::Bitmap;
 
 use crate::WorldMesh;
+use pikaxe_bevy::prelude::{MiloPickable, MiloWorldSettings};
 use super::{ImageInfo, map_matrix, MiloLoader, TextureEncoding};
 
 pub fn render_milo_entry(
@@ -29,6 +30,7 @@ pub fn render_milo_entry(
     milo_path: &Path,
     milo_entry: Option<String>,
     system_info: &SystemInfo,
+    world_settings: &MiloWorldSettings,
 ) {
     let mut loader = MiloLoader::new(milo, milo_path);
 
@@ -54,17 +56,7 @@ pub fn render_milo_entry(
     };
 
     // Translate to bevy coordinate system
-    let trans_mat = Mat4::from_cols_array(&[
-        -1.0,  0.0,  0.0, 0.0,
-        0.0,  0.0,  1.0, 0.0,
-        0.0,  1.0,  0.0, 0.0,
-        0.0,  0.0,  0.0, 1.0,
-    ]);
-
-    // Scale down
-    let scale_mat = Mat4::from_scale(Vec3::new(0.1, 0.1, 0.1));
-
-    let trans = Transform::from_matrix(trans_mat * scale_mat);
+    let trans = Transform::from_matrix(world_settings.get_world_matrix());
     let global_trans = GlobalTransform::from(trans);
 
     // Root transform
@@ -179,7 +171,7 @@ pub fn render_milo_entry(
                     name: mesh.name.to_owned(),
                     vert_count: mesh.vertices.len(),
                     face_count: mesh.faces.len()
-                });
+                }).insert(MiloPickable);
             });
     }
 }
@@ -289,7 +281,7 @@ fn get_object_meshes_with_transform<'a>(
     meshes
 }
 
-fn get_computed_mat<'a>(
+pub(super) fn get_computed_mat<'a>(
     milo_object: &'a dyn Trans,
     loader: &mut MiloLoader<'a>,
 ) -> Mat4 {
@@ -1,12 +1,18 @@
+mod gltf;
 mod loader;
 mod milo_entry;
+mod model;
+mod texture;
 
 use bevy::asset::RenderAssetUsages;
 use bevy::prelude::*;
 use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
 
+pub use gltf::*;
 pub use loader::*;
 pub use milo_entry::*;
+pub use model::*;
+pub use texture::*;
 
 use log::{debug, info, warn, error};
 
@@ -17,30 +23,156 @@ use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 use pikaxe::{Platform, SystemInfo};
+use pikaxe_bevy::prelude::MiloWorldSettings;
 use pikaxe::io::*;
 use pikaxe::scene::{RndMesh, Matrix, MeshObject, MiloObject, Object, ObjectDir, PackedObject, Tex, Trans, TransConstraint};
 
-pub fn open_and_unpack_milo<T: AsRef<Path>>(milo_path: T) -> Result<(ObjectDir, SystemInfo), Box<dyn Error>> {
+pub fn open_and_unpack_milo<T: AsRef<Path>>(milo_path: T) -> Result<(ObjectDir, SystemInfo, BlockType), Box<dyn Error>> {
     let milo_path = milo_path.as_ref();
 
     let mut stream = FileStream::from_path_as_read_open(milo_path)?;
     let milo = MiloArchive::from_stream(&mut stream)?;
 
+    unpack_milo(milo, milo_path)
+}
+
+/// Unpacks milo read from memory (i.e. ark entry)
+pub fn unpack_milo_from_bytes<T: AsRef<Path>>(data: &[u8], milo_path: T) -> Result<(ObjectDir, SystemInfo, BlockType), Box<dyn Error>> {
+    let mut stream = MemoryStream::from_slice_as_read(data);
+    let milo = MiloArchive::from_stream(&mut stream)?;
+
+    unpack_milo(milo, milo_path.as_ref())
+}
+
+fn unpack_milo(milo: MiloArchive, milo_path: &Path) -> Result<(ObjectDir, SystemInfo, BlockType), Box<dyn Error>> {
     let system_info = SystemInfo::guess_system_info(&milo, &milo_path);
+    let block_type = milo.get_block_type();
     let mut obj_dir = milo.unpack_directory(&system_info)?;
 
-    // Remove unsupported entries
+    // Leave unsupported entries packed so they're preserved on save
+    let mut packed_entries = Vec::new();
+
     #[allow(irrefutable_let_patterns)]
     if let ObjectDir::ObjectDir(dir) = &mut obj_dir {
-        dir.entries.retain(|e| match e.get_type() {
-            "Group" | "Mat" | "Mesh" | "Tex" | "Trans" => true,
-            _ => false
-        });
+        let entries = std::mem::take(&mut dir.entries);
+
+        for (i, entry) in entries.into_iter().enumerate() {
+            match entry.get_type() {
+                "Group" | "Mat" | "Mesh" | "Tex" | "Trans" => dir.entries.push(entry),
+                _ => packed_entries.push((i, entry))
+            }
+        }
     }
 
     obj_dir.unpack_entries(&system_info)?;
 
-    Ok((obj_dir, system_info))
+    // Restore original order
+    #[allow(irrefutable_let_patterns)]
+    if let ObjectDir::ObjectDir(dir) = &mut obj_dir {
+        for (i, entry) in packed_entries {
+            dir.entries.insert(i, entry);
+        }
+    }
+
+    Ok((obj_dir, system_info, block_type))
+}
+
+pub fn pack_milo(obj_dir: &ObjectDir, system_info: &SystemInfo, block_type: BlockType) -> Result<Vec<u8>, Box<dyn Error>> {
+    let milo = MiloArchive::from_object_dir(obj_dir, system_info, Some(block_type))?;
+
+    let mut data = Vec::new();
+    let mut stream = MemoryStream::from_vector_as_read_write(&mut data);
+    milo.write_to_stream(&mut stream)?;
+
+    Ok(data)
+}
+
+pub fn save_milo<T: AsRef<Path>>(milo_path: T, obj_dir: &ObjectDir, system_info: &SystemInfo, block_type: BlockType) -> Result<(), Box<dyn Error>> {
+    let milo_path = milo_path.as_ref();
+    let data = pack_milo(obj_dir, system_info, block_type)?;
+
+    // Write to memory first so failed packing doesn't clobber file
+    fs::write(milo_path, data)?;
+    Ok(())
+}
+
+/// Saves milo for another platform. Embedded bitmaps are re-encoded while packing
+/// and restored after, so open milo is unchanged.
+pub fn save_milo_for_platform<T: AsRef<Path>>(milo_path: T, obj_dir: &mut ObjectDir, from_info: &SystemInfo, to_info: &SystemInfo, block_type: BlockType) -> Result<(), Box<dyn Error>> {
+    // Unsupported entries are kept as raw data and can't be byte swapped
+    let packed_count = obj_dir
+        .get_entries()
+        .iter()
+        .filter(|e| matches!(e, Object::Packed(_)))
+        .count();
+
+    let endian_changed = from_info.platform.eq(&Platform::PS2) != to_info.platform.eq(&Platform::PS2);
+
+    if endian_changed && packed_count > 0 {
+        return Err(format!("Can't convert {packed_count} unsupported entries to other endianness").into());
+    }
+
+    let mut original_bitmaps = Vec::new();
+
+    for entry in get_entries_mut(obj_dir).iter_mut() {
+        let Object::Tex(tex) = entry else {
+            continue;
+        };
+
+        let Some(bitmap) = tex.bitmap.as_ref() else {
+            continue;
+        };
+
+        let image = decode_bitmap(bitmap, from_info)
+            .and_then(|t| t.mips.into_iter().next())
+            .and_then(|m| image::RgbaImage::from_raw(m.width, m.height, m.rgba));
+
+        let Some(image) = image else {
+            warn!("Unable to decode \"{}\", bitmap not converted", &tex.name);
+            continue;
+        };
+
+        let new_bitmap = encode_bitmap(&image, bitmap, to_info);
+        original_bitmaps.push((tex.name.to_owned(), tex.bitmap.replace(new_bitmap)));
+    }
+
+    let res = save_milo(milo_path, obj_dir, to_info, block_type);
+
+    // Restore original bitmaps
+    for entry in get_entries_mut(obj_dir).iter_mut() {
+        let Object::Tex(tex) = entry else {
+            continue;
+        };
+
+        if let Some((_, bitmap)) = original_bitmaps.iter_mut().find(|(n, _)| n.eq(&tex.name)) {
+            tex.bitmap = bitmap.take();
+        }
+    }
+
+    res
+}
+
+fn get_entries_mut(obj_dir: &mut ObjectDir) -> &mut Vec<Object> {
+    match obj_dir {
+        ObjectDir::ObjectDir(dir) => &mut dir.entries,
+    }
+}
+
+/// Unpacks + re-packs milo without changes, returns offset of first mismatched byte
+pub fn verify_milo_round_trip<T: AsRef<Path>>(data: &[u8], milo_path: T) -> Result<Option<usize>, Box<dyn Error>> {
+    let (obj_dir, system_info, block_type) = unpack_milo_from_bytes(data, milo_path)?;
+    let new_data = pack_milo(&obj_dir, &system_info, block_type)?;
+
+    let mismatch = data
+        .iter()
+        .zip(new_data.iter())
+        .position(|(a, b)| a.ne(b))
+        .or_else(|| match data.len().eq(&new_data.len()) {
+            true => None,
+            _ => Some(data.len().min(new_data.len()))
+        });
+
+    Ok(mismatch)
 }
 
 pub fn render_milo(
@@ -50,6 +182,7 @@ pub fn render_milo(
     bevy_textures: &mut ResMut<Assets<Image>>,
     milo: &ObjectDir,
     system_info: &SystemInfo,
+    world_settings: &MiloWorldSettings,
 ) {
     let entries = milo.get_entries();
 
@@ -185,12 +318,7 @@ pub fn render_milo(
             .unwrap_or(map_matrix(mesh.get_world_xfm()));
 
         // Translate to bevy coordinate system
-        let matrix = Mat4::from_cols_array(&[
-            -1.0,  0.0,  0.0, 0.0,
-            0.0,  0.0,  1.0, 0.0,
-            0.0,  1.0,  0.0, 0.0,
-            0.0,  0.0,  0.0, 1.0,
-        ]);
+        let world_matrix = world_settings.get_world_matrix();
 
         let mat = mats
             .iter()
@@ -245,9 +373,7 @@ pub fn render_milo(
         commands.spawn((
             Mesh3d(bevy_meshes.add(bevy_mesh)),
             MeshMaterial3d(materials.add(bevy_mat)),
-            Transform::from_matrix(base_matrix)
-                * Transform::from_matrix(matrix)
-                * Transform::from_scale(Vec3::new(0.1, 0.1, 0.1)),
+            Transform::from_matrix(world_matrix * base_matrix),
         ));
 
         debug!("Added {}", &mesh.name);
@@ -277,4 +403,15 @@ pub fn map_matrix(m: &Matrix) -> Mat4 {
         m.m43,
         m.m44,
     ])
-}
\ No newline at end of file
+}
+
+pub fn unmap_matrix(m: &Mat4) -> Matrix {
+    let v = m.to_cols_array();
+
+    Matrix {
+        m11: v[0], m12: v[1], m13: v[2], m14: v[3],
+        m21: v[4], m22: v[5], m23: v[6], m24: v[7],
+        m31: v[8], m32: v[9], m33: v[10], m34: v[11],
+        m41: v[12], m42: v[13], m43: v[14], m44: v[15],
+    }
+}
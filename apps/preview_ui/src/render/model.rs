@@ -0,0 +1,563 @@
+use bevy::prelude::*;
+use itertools::*;
+use log::warn;
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::path::Path;
+
+use pikaxe::SystemInfo;
+use pikaxe::model::open_model;
+use pikaxe::scene::{Color3, GroupObject, MatObject, MeshObject, Object, Tex, TransObject, UV, Vector4, Vert};
+use pikaxe::texture::Bitmap;
+
+use super::{encode_bitmap, import_png, map_matrix, unmap_matrix};
+
+// Indices are 16-bit
+const MAX_MESH_VERTS: usize = u16::MAX as usize;
+
+// Gltf/obj are y-up, milo is z-up
+const Y_UP_TO_Z_UP: Mat4 = Mat4::from_cols_array(&[
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 0.0, 1.0, 0.0,
+    0.0, -1.0, 0.0, 0.0,
+    0.0, 0.0, 0.0, 1.0,
+]);
+
+#[derive(Default)]
+struct ImportScene {
+    nodes: Vec<ImportNode>, // Parents always come before children
+    mats: Vec<ImportMat>,
+    textures: Vec<ImportTex>,
+}
+
+struct ImportNode {
+    name: String,
+    parent: Option<usize>,
+    local_xfm: Mat4,
+    meshes: Vec<ImportMesh>,
+}
+
+#[derive(Default)]
+struct ImportMesh {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<[f32; 2]>,
+    indices: Vec<u32>,
+    mat: Option<usize>,
+}
+
+struct ImportMat {
+    name: String,
+    color: [f32; 4],
+    diffuse: Option<usize>,
+}
+
+struct ImportTex {
+    name: String,
+    bitmap: Option<Bitmap>, // Already encoded for platform
+}
+
+/// Reads gltf/glb or obj file as milo objects. Everything is added to new group
+/// named after file and names are made unique from existing entries.
+pub fn import_model<T: AsRef<Path>>(model_path: T, system_info: &SystemInfo, existing_names: &[&str]) -> Result<Vec<Object>, Box<dyn Error>> {
+    let model_path = model_path.as_ref();
+
+    let ext = model_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    let scene = match ext.as_str() {
+        "gltf" | "glb" => read_gltf(model_path, system_info)?,
+        "obj" => read_obj(model_path, system_info)?,
+        _ => return Err(format!("Unsupported model format \"{ext}\"").into())
+    };
+
+    let file_stem = model_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("import");
+
+    let mut names = existing_names
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<HashSet<_>>();
+
+    Ok(scene.into_milo_objects(file_stem, &mut names))
+}
+
+impl ImportScene {
+    fn into_milo_objects(self, group_name: &str, names: &mut HashSet<String>) -> Vec<Object> {
+        let mut objects = Vec::new();
+
+        let tex_names = self.textures
+            .iter()
+            .map(|t| get_unique_name(&t.name, "tex", names))
+            .collect::<Vec<_>>();
+
+        for (tex, name) in self.textures.into_iter().zip(tex_names.iter()) {
+            let (width, height, bpp) = tex.bitmap
+                .as_ref()
+                .map(|b| (b.width as _, b.height as _, b.bpp as _))
+                .unwrap_or_default();
+
+            objects.push(Object::Tex(Tex {
+                name: name.to_owned(),
+                width,
+                height,
+                bpp,
+                bitmap: tex.bitmap,
+                ..Default::default()
+            }));
+        }
+
+        let mat_names = self.mats
+            .iter()
+            .map(|m| get_unique_name(&m.name, "mat", names))
+            .collect::<Vec<_>>();
+
+        for (mat, name) in self.mats.iter().zip(mat_names.iter()) {
+            let [r, g, b, alpha] = mat.color;
+
+            objects.push(Object::Mat(MatObject {
+                name: name.to_owned(),
+                color: Color3 { r, g, b },
+                alpha,
+                diffuse_tex: mat.diffuse.map(|i| tex_names[i].to_owned()).unwrap_or_default(),
+                ..Default::default()
+            }));
+        }
+
+        let group_name = get_unique_name(group_name, "grp", names);
+        let mut group_objects = Vec::new();
+
+        let mut node_names: Vec<String> = Vec::new();
+        let mut node_world_xfms: Vec<Mat4> = Vec::new();
+
+        for node in self.nodes.iter() {
+            // Convert to z-up
+            let local_xfm = Y_UP_TO_Z_UP * node.local_xfm * Y_UP_TO_Z_UP.inverse();
+
+            let (parent_name, world_xfm) = match node.parent {
+                Some(p) => (node_names[p].to_owned(), node_world_xfms[p] * local_xfm),
+                _ => (group_name.to_owned(), local_xfm)
+            };
+
+            let meshes = node.meshes
+                .iter()
+                .flat_map(|m| split_mesh(m).into_iter().map(move |(verts, faces)| (m, verts, faces)))
+                .collect::<Vec<_>>();
+
+            if meshes.is_empty() {
+                let name = get_unique_name(&node.name, "trn", names);
+
+                objects.push(Object::Trans(TransObject {
+                    name: name.to_owned(),
+                    parent: parent_name,
+                    local_xfm: unmap_matrix(&local_xfm),
+                    world_xfm: unmap_matrix(&world_xfm),
+                    ..Default::default()
+                }));
+
+                node_names.push(name);
+                node_world_xfms.push(world_xfm);
+                continue;
+            }
+
+            // Children are parented to first mesh
+            let mut first_name = None;
+
+            for (mesh, verts, faces) in meshes {
+                let name = get_unique_name(&node.name, "mesh", names);
+
+                let vertices = verts
+                    .iter()
+                    .map(|i| {
+                        let i = *i as usize;
+
+                        let pos = Y_UP_TO_Z_UP.transform_point3(mesh.positions[i]);
+                        let normal = mesh.normals
+                            .get(i)
+                            .map(|n| Y_UP_TO_Z_UP.transform_vector3(*n))
+                            .unwrap_or(Vec3::Z);
+                        let [u, v] = mesh.uvs.get(i).copied().unwrap_or_default();
+
+                        Vert {
+                            pos: Vector4 { x: pos.x, y: pos.y, z: pos.z, w: 1.0 },
+                            normals: Vector4 { x: normal.x, y: normal.y, z: normal.z, w: 0.0 },
+                            uv: UV { u, v },
+                            ..Default::default()
+                        }
+                    })
+                    .collect();
+
+                objects.push(Object::Mesh(MeshObject {
+                    name: name.to_owned(),
+                    parent: parent_name.to_owned(),
+                    local_xfm: unmap_matrix(&local_xfm),
+                    world_xfm: unmap_matrix(&world_xfm),
+                    mat: mesh.mat.map(|i| mat_names[i].to_owned()).unwrap_or_default(),
+                    vertices,
+                    faces,
+                    ..Default::default()
+                }));
+
+                group_objects.push(name.to_owned());
+                first_name.get_or_insert(name);
+            }
+
+            node_names.push(first_name.unwrap());
+            node_world_xfms.push(world_xfm);
+        }
+
+        objects.push(Object::Group(GroupObject {
+            name: group_name,
+            local_xfm: unmap_matrix(&Mat4::IDENTITY),
+            world_xfm: unmap_matrix(&Mat4::IDENTITY),
+            objects: group_objects,
+            ..Default::default()
+        }));
+
+        objects
+    }
+}
+
+fn get_unique_name(name: &str, ext: &str, names: &mut HashSet<String>) -> String {
+    // Milo names don't use spaces
+    let base = match name.trim().is_empty() {
+        true => String::from("object"),
+        _ => name.trim().replace(' ', "_")
+    };
+
+    let mut unique_name = format!("{base}.{ext}");
+    let mut i = 1;
+
+    while names.contains(&unique_name) {
+        unique_name = format!("{base}_{i}.{ext}");
+        i += 1;
+    }
+
+    names.insert(unique_name.to_owned());
+    unique_name
+}
+
+/// Splits mesh so each part fits in 16-bit indices. Returns source vertex
+/// indices and faces for each part.
+fn split_mesh(mesh: &ImportMesh) -> Vec<(Vec<u32>, Vec<[u16; 3]>)> {
+    let mut parts = Vec::new();
+
+    let mut vert_map = HashMap::new();
+    let mut verts = Vec::new();
+    let mut faces = Vec::new();
+
+    for tri in mesh.indices.chunks_exact(3) {
+        let new_count = tri
+            .iter()
+            .unique()
+            .filter(|i| !vert_map.contains_key(*i))
+            .count();
+
+        if verts.len() + new_count > MAX_MESH_VERTS {
+            parts.push((std::mem::take(&mut verts), std::mem::take(&mut faces)));
+            vert_map.clear();
+        }
+
+        let mut face = [0u16; 3];
+
+        for (f, i) in face.iter_mut().zip(tri) {
+            *f = *vert_map.entry(*i).or_insert_with(|| {
+                verts.push(*i);
+                (verts.len() - 1) as u16
+            });
+        }
+
+        faces.push(face);
+    }
+
+    if !faces.is_empty() {
+        parts.push((verts, faces));
+    }
+
+    if parts.len() > 1 {
+        warn!("Split mesh into {} parts to fit 16-bit indices", parts.len());
+    }
+
+    parts
+}
+
+fn read_gltf(gltf_path: &Path, system_info: &SystemInfo) -> Result<ImportScene, Box<dyn Error>> {
+    // Read with pikaxe model importer, meshes are re-built (and split) same as obj
+    let assets = open_model(gltf_path, system_info.to_owned())?;
+    let mut scene = ImportScene::default();
+
+    let tex_names = assets.get_textures()
+        .iter()
+        .map(|t| t.name.as_str())
+        .collect::<Vec<_>>();
+
+    for tex in assets.get_textures() {
+        scene.textures.push(ImportTex {
+            name: get_base_name(&tex.name).to_owned(),
+            bitmap: tex.bitmap.to_owned(),
+        });
+    }
+
+    let mat_names = assets.get_materials()
+        .iter()
+        .map(|m| m.name.as_str())
+        .collect::<Vec<_>>();
+
+    for mat in assets.get_materials() {
+        scene.mats.push(ImportMat {
+            name: get_base_name(&mat.name).to_owned(),
+            color: [mat.color.r, mat.color.g, mat.color.b, mat.alpha],
+            diffuse: tex_names.iter().position(|t| t.eq(&mat.diffuse_tex)),
+        });
+    }
+
+    let mut pending = assets.get_groups()
+        .iter()
+        .map(|g| (g.name.as_str(), g.parent.as_str(), &g.local_xfm, None))
+        .chain(assets.get_meshes()
+            .iter()
+            .map(|m| (m.name.as_str(), m.parent.as_str(), &m.local_xfm, Some(m))))
+        .collect::<Vec<_>>();
+
+    let mut node_indices = HashMap::new();
+
+    // Add parents before children
+    while !pending.is_empty() {
+        let next = pending
+            .iter()
+            .position(|(_, parent, ..)| node_indices.contains_key(parent)
+                || !pending.iter().any(|(name, ..)| name.eq(parent)));
+
+        let Some(next) = next else {
+            warn!("Skipping {} objects with cyclic parents", pending.len());
+            break;
+        };
+
+        let (name, parent, local_xfm, mesh) = pending.remove(next);
+
+        scene.nodes.push(ImportNode {
+            name: get_base_name(name).to_owned(),
+            parent: node_indices.get(parent).copied(),
+            local_xfm: map_matrix(local_xfm),
+            meshes: mesh
+                .map(|m| vec![get_import_mesh(m, mat_names.iter().position(|n| n.eq(&m.mat)))])
+                .unwrap_or_default(),
+        });
+
+        node_indices.insert(name, scene.nodes.len() - 1);
+    }
+
+    Ok(scene)
+}
+
+fn get_import_mesh(mesh: &MeshObject, mat: Option<usize>) -> ImportMesh {
+    ImportMesh {
+        positions: mesh.vertices.iter().map(|v| Vec3::new(v.pos.x, v.pos.y, v.pos.z)).collect(),
+        normals: mesh.vertices.iter().map(|v| Vec3::new(v.normals.x, v.normals.y, v.normals.z)).collect(),
+        uvs: mesh.vertices.iter().map(|v| [v.uv.u, v.uv.v]).collect(),
+        indices: mesh.faces.iter().flatten().map(|i| *i as u32).collect(),
+        mat,
+    }
+}
+
+fn get_base_name(name: &str) -> &str {
+    // Drop milo extension (i.e. ".mesh"), added back when made unique
+    name.rsplit_once('.').map(|(b, _)| b).unwrap_or(name)
+}
+
+#[derive(Default)]
+struct ObjMeshBuilder {
+    mesh: ImportMesh,
+    vert_map: HashMap<(usize, Option<usize>, Option<usize>), u32>,
+}
+
+fn read_obj(obj_path: &Path, system_info: &SystemInfo) -> Result<ImportScene, Box<dyn Error>> {
+    let text = std::fs::read_to_string(obj_path)?;
+    let obj_dir = obj_path.parent().unwrap_or(Path::new(""));
+
+    let mut scene = ImportScene::default();
+    let mut mat_indices = HashMap::new();
+
+    let (mut positions, mut uvs, mut normals) = (Vec::new(), Vec::new(), Vec::new());
+
+    let mut node = new_obj_node(obj_path.file_stem().and_then(|s| s.to_str()).unwrap_or("obj"));
+    let mut builder = ObjMeshBuilder::default();
+
+    let parse_vec3 = |values: &[&str]| {
+        let mut v = [0.0f32; 3];
+
+        for (v, s) in v.iter_mut().zip(values) {
+            *v = s.parse().unwrap_or_default();
+        }
+
+        Vec3::from(v)
+    };
+
+    for line in text.lines() {
+        let values = line.split_whitespace().collect::<Vec<_>>();
+        let Some((key, values)) = values.split_first() else {
+            continue;
+        };
+
+        match *key {
+            "v" => positions.push(parse_vec3(values)),
+            "vt" => {
+                // Obj uv origin is bottom left
+                let v = parse_vec3(values);
+                uvs.push([v.x, 1.0 - v.y]);
+            },
+            "vn" => normals.push(parse_vec3(values)),
+            "f" => {
+                let face = values
+                    .iter()
+                    .map(|v| {
+                        let mut idx = v.split('/').map(|i| i.parse::<i64>().ok());
+                        (
+                            get_obj_index(idx.next().flatten(), positions.len()).unwrap_or_default(),
+                            get_obj_index(idx.next().flatten(), uvs.len()),
+                            get_obj_index(idx.next().flatten(), normals.len()),
+                        )
+                    })
+                    .map(|key| *builder.vert_map.entry(key).or_insert_with(|| {
+                        let (p, uv, n) = key;
+                        let mesh = &mut builder.mesh;
+
+                        mesh.positions.push(positions.get(p).copied().unwrap_or_default());
+                        mesh.uvs.push(uv.and_then(|i| uvs.get(i)).copied().unwrap_or_default());
+                        mesh.normals.push(n.and_then(|i| normals.get(i)).copied().unwrap_or(Vec3::Y));
+
+                        (mesh.positions.len() - 1) as u32
+                    }))
+                    .collect::<Vec<_>>();
+
+                // Triangulate as fan
+                for i in 1..(face.len().saturating_sub(1)) {
+                    builder.mesh.indices.extend([face[0], face[i], face[i + 1]]);
+                }
+            },
+            "o" | "g" => {
+                flush_obj_mesh(&mut builder, &mut node);
+
+                if !node.meshes.is_empty() {
+                    scene.nodes.push(node);
+                }
+
+                node = new_obj_node(&values.join("_"));
+            },
+            "usemtl" => {
+                let mat = mat_indices.get(&values.join(" ")).copied();
+                flush_obj_mesh(&mut builder, &mut node);
+
+                builder.mesh.mat = mat;
+            },
+            "mtllib" => {
+                let mtl_path = obj_dir.join(values.join(" "));
+
+                if let Err(err) = read_mtl(&mtl_path, system_info, &mut scene, &mut mat_indices) {
+                    warn!("Unable to read \"{}\": {:?}", mtl_path.display(), err);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    flush_obj_mesh(&mut builder, &mut node);
+
+    if !node.meshes.is_empty() {
+        scene.nodes.push(node);
+    }
+
+    Ok(scene)
+}
+
+fn new_obj_node(name: &str) -> ImportNode {
+    ImportNode {
+        name: name.to_owned(),
+        parent: None,
+        local_xfm: Mat4::IDENTITY,
+        meshes: Vec::new(),
+    }
+}
+
+fn flush_obj_mesh(builder: &mut ObjMeshBuilder, node: &mut ImportNode) {
+    let ObjMeshBuilder { mesh, .. } = std::mem::take(builder);
+
+    // Keep material for next faces
+    builder.mesh.mat = mesh.mat;
+
+    if !mesh.indices.is_empty() {
+        node.meshes.push(mesh);
+    }
+}
+
+fn get_obj_index(idx: Option<i64>, count: usize) -> Option<usize> {
+    // 1-based, negative values are relative to end
+    match idx? {
+        i if i < 0 => (count as i64 + i).try_into().ok(),
+        i => (i - 1).try_into().ok(),
+    }
+}
+
+fn read_mtl(mtl_path: &Path, system_info: &SystemInfo, scene: &mut ImportScene, mat_indices: &mut HashMap<String, usize>) -> Result<(), Box<dyn Error>> {
+    let text = std::fs::read_to_string(mtl_path)?;
+    let mtl_dir = mtl_path.parent().unwrap_or(Path::new(""));
+
+    for line in text.lines() {
+        let values = line.split_whitespace().collect::<Vec<_>>();
+        let Some((key, values)) = values.split_first() else {
+            continue;
+        };
+
+        if key.eq(&"newmtl") {
+            let name = values.join(" ");
+
+            scene.mats.push(ImportMat {
+                name: name.to_owned(),
+                color: [1.0, 1.0, 1.0, 1.0],
+                diffuse: None,
+            });
+
+            mat_indices.insert(name, scene.mats.len() - 1);
+            continue;
+        }
+
+        let Some(mat) = scene.mats.last_mut() else {
+            continue;
+        };
+
+        let value = |i: usize| values.get(i).and_then(|v| v.parse::<f32>().ok()).unwrap_or(1.0);
+
+        match *key {
+            "Kd" => {
+                mat.color = [value(0), value(1), value(2), mat.color[3]];
+            },
+            "d" => mat.color[3] = value(0),
+            "Tr" => mat.color[3] = 1.0 - value(0),
+            "map_Kd" => {
+                // Options before path aren't supported
+                let tex_path = mtl_dir.join(values.last().copied().unwrap_or_default());
+
+                match import_png(&tex_path) {
+                    Ok(image) => {
+                        scene.textures.push(ImportTex {
+                            name: tex_path.file_stem().and_then(|s| s.to_str()).unwrap_or("tex").to_owned(),
+                            bitmap: Some(encode_bitmap(&image, &Bitmap::default(), system_info)),
+                        });
+
+                        mat.diffuse = Some(scene.textures.len() - 1);
+                    },
+                    Err(err) => warn!("Unable to open \"{}\": {:?}", tex_path.display(), err),
+                }
+            },
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,525 @@
+use image::RgbaImage;
+use image::imageops::{self, FilterType};
+use pikaxe::{Platform, SystemInfo};
+use pikaxe::texture::Bitmap;
+use std::error::Error;
+use std::path::Path;
+
+use super::TextureEncoding;
+
+const MAX_TEXTURE_SIZE: u32 = 2048;
+
+pub struct DecodedMip {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+pub struct DecodedTexture {
+    pub encoding: TextureEncoding,
+    pub mips: Vec<DecodedMip>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum EncodeFormat {
+    RGBA,
+    DXT1,
+    DXT5,
+    ATI2,
+    CMPR, // Wii
+}
+
+/// Decodes bitmap + mips to RGBA on the CPU
+pub fn decode_bitmap(bitmap: &Bitmap, system_info: &SystemInfo) -> Option<DecodedTexture> {
+    let (width, height) = (bitmap.width as u32, bitmap.height as u32);
+    let mip_count = (bitmap.mip_maps as usize) + 1;
+
+    let encoding = match (system_info.platform, bitmap.encoding) {
+        (Platform::X360 | Platform::PS3, 8) => TextureEncoding::DXT1,
+        (Platform::X360 | Platform::PS3, 24) => TextureEncoding::DXT5,
+        (Platform::X360 | Platform::PS3, 32) => TextureEncoding::ATI2,
+        _ => TextureEncoding::RGBA,
+    };
+
+    let (data, block_size) = match encoding {
+        TextureEncoding::RGBA => {
+            let rgba = bitmap.unpack_rgba(system_info).ok()?;
+            let mips = split_mips(width, height, mip_count, &rgba, |w, h| (w * h * 4) as usize)
+                .into_iter()
+                .map(|(w, h, d)| DecodedMip { width: w, height: h, rgba: d.to_owned() })
+                .collect();
+
+            return Some(DecodedTexture { encoding, mips });
+        },
+        TextureEncoding::DXT1 => (bitmap.raw_data.to_owned(), 8),
+        TextureEncoding::DXT5 | TextureEncoding::ATI2 => (bitmap.raw_data.to_owned(), 16),
+    };
+
+    let mut data = data;
+
+    if system_info.platform.eq(&Platform::X360) {
+        // Swap bytes
+        for ab in data.chunks_exact_mut(2) {
+            ab.swap(0, 1);
+        }
+    }
+
+    let block_fn = match encoding {
+        TextureEncoding::DXT1 => decode_bc1_block,
+        TextureEncoding::DXT5 => decode_bc3_block,
+        _ => decode_bc5_block,
+    };
+
+    let mips = split_mips(width, height, mip_count, &data, |w, h| (w.div_ceil(4) * h.div_ceil(4) * block_size) as usize)
+        .into_iter()
+        .map(|(w, h, d)| DecodedMip {
+            width: w,
+            height: h,
+            rgba: decode_blocks(w, h, d, block_size as usize, block_fn),
+        })
+        .collect();
+
+    Some(DecodedTexture { encoding, mips })
+}
+
+pub fn export_png<T: AsRef<Path>>(png_path: T, mip: &DecodedMip) -> Result<(), Box<dyn Error>> {
+    image::save_buffer(
+        png_path.as_ref(),
+        &mip.rgba,
+        mip.width,
+        mip.height,
+        image::ColorType::Rgba8
+    )?;
+
+    Ok(())
+}
+
+/// Encodes mip as png in memory
+pub fn encode_png(mip: &DecodedMip) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut data = Vec::new();
+
+    image::write_buffer_with_format(
+        &mut std::io::Cursor::new(&mut data),
+        &mip.rgba,
+        mip.width,
+        mip.height,
+        image::ColorType::Rgba8,
+        image::ImageFormat::Png
+    )?;
+
+    Ok(data)
+}
+
+pub fn import_png<T: AsRef<Path>>(png_path: T) -> Result<RgbaImage, Box<dyn Error>> {
+    let image = image::open(png_path.as_ref())?;
+    Ok(image.to_rgba8())
+}
+
+/// Encodes image + generated mips as new bitmap. Format is picked from platform
+/// and existing bitmap, other properties are kept.
+pub fn encode_bitmap(image: &RgbaImage, template: &Bitmap, system_info: &SystemInfo) -> Bitmap {
+    // Textures need power of 2 dimensions
+    let width = image.width().max(1).next_power_of_two().min(MAX_TEXTURE_SIZE);
+    let height = image.height().max(1).next_power_of_two().min(MAX_TEXTURE_SIZE);
+    let has_alpha = image.pixels().any(|p| p[3] < 255);
+
+    let format = match (system_info.platform, template.encoding) {
+        (Platform::X360 | Platform::PS3, 32) => EncodeFormat::ATI2, // Keep normal maps as normal maps
+        (Platform::X360 | Platform::PS3, _) if has_alpha => EncodeFormat::DXT5,
+        (Platform::X360 | Platform::PS3, _) => EncodeFormat::DXT1,
+        (Platform::Wii, _) => EncodeFormat::CMPR,
+        _ => EncodeFormat::RGBA,
+    };
+
+    let (encoding, bpp) = match format {
+        EncodeFormat::RGBA => (3, 32),
+        EncodeFormat::DXT1 => (8, 4),
+        EncodeFormat::DXT5 => (24, 8),
+        EncodeFormat::ATI2 => (32, 8),
+        EncodeFormat::CMPR => (72, 4),
+    };
+
+    let mut raw_data = Vec::new();
+    let (mut w, mut h, mut mip_maps) = (width, height, 0);
+
+    loop {
+        let mip = match image.dimensions() == (w, h) {
+            true => image.to_owned(),
+            _ => imageops::resize(image, w, h, FilterType::Triangle)
+        };
+
+        raw_data.append(&mut encode_mip(&mip, format, system_info.platform));
+
+        if w == 1 && h == 1 {
+            break;
+        }
+
+        w = (w / 2).max(1);
+        h = (h / 2).max(1);
+        mip_maps += 1;
+    }
+
+    if system_info.platform.eq(&Platform::X360) && format.ne(&EncodeFormat::RGBA) {
+        // Swap bytes
+        for ab in raw_data.chunks_exact_mut(2) {
+            ab.swap(0, 1);
+        }
+    }
+
+    let mut bitmap = template.clone();
+    bitmap.width = width as _;
+    bitmap.height = height as _;
+    bitmap.bpp = bpp as _;
+    bitmap.bpl = ((width * bpp) / 8) as _;
+    bitmap.encoding = encoding as _;
+    bitmap.mip_maps = mip_maps as _;
+    bitmap.raw_data = raw_data;
+
+    bitmap
+}
+
+fn encode_mip(mip: &RgbaImage, format: EncodeFormat, platform: Platform) -> Vec<u8> {
+    match format {
+        EncodeFormat::RGBA if platform.eq(&Platform::PS2) => {
+            // PS2 alpha is 0-128
+            mip.pixels()
+                .flat_map(|p| [p[0], p[1], p[2], ((p[3] as u32 + 1) / 2) as u8])
+                .collect()
+        },
+        EncodeFormat::RGBA => mip.as_raw().to_owned(),
+        EncodeFormat::DXT1 => encode_blocks(mip, encode_bc1_block),
+        EncodeFormat::DXT5 => encode_blocks(mip, encode_bc3_block),
+        EncodeFormat::ATI2 => encode_blocks(mip, encode_bc5_block),
+        EncodeFormat::CMPR => encode_cmpr_tiles(mip),
+    }
+}
+
+fn split_mips(width: u32, height: u32, mip_count: usize, data: &[u8], get_size: impl Fn(u32, u32) -> usize) -> Vec<(u32, u32, &[u8])> {
+    let mut mips = Vec::new();
+    let (mut w, mut h, mut offset) = (width, height, 0);
+
+    // Stop early if data is missing mips
+    while mips.len() < mip_count && w > 0 && h > 0 {
+        let size = get_size(w, h);
+
+        if offset + size > data.len() {
+            break;
+        }
+
+        mips.push((w, h, &data[offset..(offset + size)]));
+
+        offset += size;
+        w = (w / 2).max(1);
+        h = (h / 2).max(1);
+
+        if mips.last().is_some_and(|(lw, lh, _)| *lw == 1 && *lh == 1) {
+            break;
+        }
+    }
+
+    mips
+}
+
+fn decode_blocks(width: u32, height: u32, data: &[u8], block_size: usize, block_fn: fn(&[u8]) -> [[u8; 4]; 16]) -> Vec<u8> {
+    let (width, height) = (width as usize, height as usize);
+    let blocks_x = width.div_ceil(4);
+
+    let mut rgba = vec![0u8; width * height * 4];
+
+    for (i, block) in data.chunks_exact(block_size).enumerate() {
+        let (bx, by) = ((i % blocks_x) * 4, (i / blocks_x) * 4);
+        let pixels = block_fn(block);
+
+        for (j, pixel) in pixels.iter().enumerate() {
+            let (x, y) = (bx + (j % 4), by + (j / 4));
+
+            // Blocks can extend past small mips
+            if x >= width || y >= height {
+                continue;
+            }
+
+            let idx = (y * width + x) * 4;
+            rgba[idx..(idx + 4)].copy_from_slice(pixel);
+        }
+    }
+
+    rgba
+}
+
+fn get_block(image: &RgbaImage, x: u32, y: u32) -> [[u8; 4]; 16] {
+    let mut pixels = [[0u8; 4]; 16];
+
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        // Clamp for mips smaller than block
+        let px = (x + (i as u32 % 4)).min(image.width() - 1);
+        let py = (y + (i as u32 / 4)).min(image.height() - 1);
+
+        *pixel = image.get_pixel(px, py).0;
+    }
+
+    pixels
+}
+
+fn encode_blocks(image: &RgbaImage, block_fn: fn(&[[u8; 4]; 16]) -> Vec<u8>) -> Vec<u8> {
+    let mut data = Vec::new();
+
+    for y in (0..image.height()).step_by(4) {
+        for x in (0..image.width()).step_by(4) {
+            data.append(&mut block_fn(&get_block(image, x, y)));
+        }
+    }
+
+    data
+}
+
+fn encode_cmpr_tiles(image: &RgbaImage) -> Vec<u8> {
+    // First pixel of each row is stored in high bits
+    let reverse_row = |b: u8| ((b & 0b11) << 6) | (((b >> 2) & 0b11) << 4) | (((b >> 4) & 0b11) << 2) | (b >> 6);
+
+    let mut data = Vec::new();
+
+    // 8x8 tiles of 2x2 dxt1 blocks
+    for y in (0..image.height()).step_by(8) {
+        for x in (0..image.width()).step_by(8) {
+            for (bx, by) in [(0, 0), (4, 0), (0, 4), (4, 4)] {
+                let (bx, by) = ((x + bx).min(image.width() - 1), (y + by).min(image.height() - 1));
+                let block = encode_color_block(&get_block(image, bx, by), true);
+
+                // Big endian colors
+                data.extend_from_slice(&[
+                    block[1], block[0], block[3], block[2],
+                    reverse_row(block[4]), reverse_row(block[5]), reverse_row(block[6]), reverse_row(block[7]),
+                ]);
+            }
+        }
+    }
+
+    data
+}
+
+fn get_nearest<const N: usize>(palette: &[[u8; N]], value: &[u8; N]) -> usize {
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, p)| p
+            .iter()
+            .zip(value.iter())
+            .map(|(a, b)| (*a as i32 - *b as i32).pow(2))
+            .sum::<i32>())
+        .map(|(i, _)| i)
+        .unwrap_or_default()
+}
+
+fn encode_color_block(pixels: &[[u8; 4]; 16], allow_transparent: bool) -> [u8; 8] {
+    // Transparent pixels use 1-bit alpha mode (dxt1 only)
+    let is_transparent = |p: &[u8; 4]| allow_transparent && p[3] < 128;
+    let has_transparent = pixels.iter().any(is_transparent);
+
+    let (mut min, mut max) = ([255u8; 3], [0u8; 3]);
+
+    for p in pixels.iter().filter(|p| !is_transparent(p)) {
+        for i in 0..3 {
+            min[i] = min[i].min(p[i]);
+            max[i] = max[i].max(p[i]);
+        }
+    }
+
+    let (mut c0, mut c1) = (encode_rgb565(max), encode_rgb565(min));
+
+    // Color order selects mode
+    if has_transparent == (c0 > c1) {
+        std::mem::swap(&mut c0, &mut c1);
+    }
+
+    let color_count = if c0 > c1 || !allow_transparent { 4 } else { 3 };
+    let palette = get_color_palette(c0, c1, !allow_transparent)
+        .map(|c| [c[0], c[1], c[2]]);
+
+    let indices = pixels
+        .iter()
+        .enumerate()
+        .fold(0u32, |acc, (i, p)| {
+            let idx = match is_transparent(p) {
+                true => 3,
+                _ => get_nearest(&palette[..color_count], &[p[0], p[1], p[2]])
+            };
+
+            acc | ((idx as u32) << (i * 2))
+        });
+
+    let mut block = [0u8; 8];
+    block[..2].copy_from_slice(&c0.to_le_bytes());
+    block[2..4].copy_from_slice(&c1.to_le_bytes());
+    block[4..].copy_from_slice(&indices.to_le_bytes());
+
+    block
+}
+
+fn encode_alpha_block(values: [u8; 16]) -> [u8; 8] {
+    let a0 = values.iter().max().copied().unwrap_or_default();
+    let a1 = values.iter().min().copied().unwrap_or_default();
+
+    let palette = get_alpha_palette(a0, a1).map(|v| [v]);
+
+    let indices = values
+        .iter()
+        .enumerate()
+        .fold(0u64, |acc, (i, v)| acc | ((get_nearest(&palette, &[*v]) as u64) << (i * 3)));
+
+    let mut block = [0u8; 8];
+    block[0] = a0;
+    block[1] = a1;
+    block[2..].copy_from_slice(&indices.to_le_bytes()[..6]);
+
+    block
+}
+
+fn encode_bc1_block(pixels: &[[u8; 4]; 16]) -> Vec<u8> {
+    encode_color_block(pixels, true).to_vec()
+}
+
+fn encode_bc3_block(pixels: &[[u8; 4]; 16]) -> Vec<u8> {
+    let alphas = pixels.map(|p| p[3]);
+
+    [encode_alpha_block(alphas), encode_color_block(pixels, false)].concat()
+}
+
+fn encode_bc5_block(pixels: &[[u8; 4]; 16]) -> Vec<u8> {
+    let (reds, greens) = (pixels.map(|p| p[0]), pixels.map(|p| p[1]));
+
+    [encode_alpha_block(reds), encode_alpha_block(greens)].concat()
+}
+
+fn encode_rgb565(rgb: [u8; 3]) -> u16 {
+    (((rgb[0] as u16) >> 3) << 11) | (((rgb[1] as u16) >> 2) << 5) | ((rgb[2] as u16) >> 3)
+}
+
+fn decode_rgb565(c: u16) -> [u8; 3] {
+    let r = ((c >> 11) & 0x1F) as u32;
+    let g = ((c >> 5) & 0x3F) as u32;
+    let b = (c & 0x1F) as u32;
+
+    [
+        ((r * 255 + 15) / 31) as u8,
+        ((g * 255 + 31) / 63) as u8,
+        ((b * 255 + 15) / 31) as u8,
+    ]
+}
+
+fn get_color_palette(c0: u16, c1: u16, force_four_colors: bool) -> [[u8; 4]; 4] {
+    let (rgb0, rgb1) = (decode_rgb565(c0), decode_rgb565(c1));
+    let mix = |a: u8, b: u8, wa: u32, wb: u32| ((a as u32 * wa + b as u32 * wb) / (wa + wb)) as u8;
+
+    let mut colors = [[0u8; 4]; 4];
+    colors[0] = [rgb0[0], rgb0[1], rgb0[2], 255];
+    colors[1] = [rgb1[0], rgb1[1], rgb1[2], 255];
+
+    if c0 > c1 || force_four_colors {
+        for i in 0..3 {
+            colors[2][i] = mix(rgb0[i], rgb1[i], 2, 1);
+            colors[3][i] = mix(rgb0[i], rgb1[i], 1, 2);
+        }
+
+        colors[2][3] = 255;
+        colors[3][3] = 255;
+    } else {
+        // 1-bit alpha mode
+        for i in 0..3 {
+            colors[2][i] = mix(rgb0[i], rgb1[i], 1, 1);
+        }
+
+        colors[2][3] = 255;
+        colors[3] = [0, 0, 0, 0];
+    }
+
+    colors
+}
+
+fn get_alpha_palette(a0: u8, a1: u8) -> [u8; 8] {
+    let (a0, a1) = (a0 as u32, a1 as u32);
+
+    let mut values = [0u8; 8];
+    values[0] = a0 as u8;
+    values[1] = a1 as u8;
+
+    if a0 > a1 {
+        for i in 1..7 {
+            values[i + 1] = ((a0 * (7 - i as u32) + a1 * i as u32) / 7) as u8;
+        }
+    } else {
+        for i in 1..5 {
+            values[i + 1] = ((a0 * (5 - i as u32) + a1 * i as u32) / 5) as u8;
+        }
+
+        values[6] = 0;
+        values[7] = 255;
+    }
+
+    values
+}
+
+fn decode_color_block(block: &[u8], force_four_colors: bool) -> [[u8; 4]; 16] {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+
+    let colors = get_color_palette(c0, c1, force_four_colors);
+    let mut pixels = [[0u8; 4]; 16];
+
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        *pixel = colors[((indices >> (i * 2)) & 0b11) as usize];
+    }
+
+    pixels
+}
+
+fn decode_alpha_block(block: &[u8]) -> [u8; 16] {
+    let values = get_alpha_palette(block[0], block[1]);
+
+    // 48-bit index table (3 bits per pixel)
+    let indices = block[2..8]
+        .iter()
+        .rev()
+        .fold(0u64, |acc, b| (acc << 8) | (*b as u64));
+
+    let mut alphas = [0u8; 16];
+
+    for (i, alpha) in alphas.iter_mut().enumerate() {
+        *alpha = values[((indices >> (i * 3)) & 0b111) as usize];
+    }
+
+    alphas
+}
+
+fn decode_bc1_block(block: &[u8]) -> [[u8; 4]; 16] {
+    decode_color_block(block, false)
+}
+
+fn decode_bc3_block(block: &[u8]) -> [[u8; 4]; 16] {
+    let alphas = decode_alpha_block(&block[..8]);
+    let mut pixels = decode_color_block(&block[8..], true);
+
+    for (pixel, alpha) in pixels.iter_mut().zip(alphas) {
+        pixel[3] = alpha;
+    }
+
+    pixels
+}
+
+fn decode_bc5_block(block: &[u8]) -> [[u8; 4]; 16] {
+    let reds = decode_alpha_block(&block[..8]);
+    let greens = decode_alpha_block(&block[8..]);
+
+    let mut pixels = [[0u8; 4]; 16];
+
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        // Two channel normal map, reconstruct z
+        let x = (reds[i] as f32 / 127.5) - 1.0;
+        let y = (greens[i] as f32 / 127.5) - 1.0;
+        let z = (1.0 - (x * x) - (y * y)).max(0.0).sqrt();
+
+        *pixel = [reds[i], greens[i], ((z + 1.0) * 127.5) as u8, 255];
+    }
+
+    pixels
+}
@@ -47,6 +47,8 @@ pub struct AppSettings {
     pub maximized: bool,
     pub show_gridlines: bool,
     pub show_wireframes: bool,
+    #[serde(default)]
+    pub recent_dirs: Vec<String>,
 }
 
 impl Default for AppSettings {
@@ -59,6 +61,7 @@ impl Default for AppSettings {
             maximized: false,
             show_gridlines: true,
             show_wireframes: false,
+            recent_dirs: Vec::new(),
         }
     }
 }
@@ -86,6 +89,17 @@ impl AppSettings {
         AppSettings::default()
     }
 
+    pub fn add_recent_dir<T>(&mut self, dir_path: T) where T: AsRef<Path> {
+        const MAX_RECENT_DIRS: usize = 10;
+
+        let dir_path = dir_path.as_ref().to_string_lossy().to_string();
+
+        // Move to front
+        self.recent_dirs.retain(|d| d.ne(&dir_path));
+        self.recent_dirs.insert(0, dir_path);
+        self.recent_dirs.truncate(MAX_RECENT_DIRS);
+    }
+
     pub fn save_to_file<T>(&self, json_path: T) where T: AsRef<Path> {
         #[cfg(not(target_family = "wasm"))] {
             let json_path = json_path.as_ref();
@@ -1,11 +1,14 @@
-use super::{AppSettings, AppEvent};
+use super::{AppSettings, AppEvent, AppFileEvent, EditCommand, EditHistory};
+use crate::render::DecodedTexture;
+use bevy_egui::egui;
 use bevy::prelude::*;
 use pikaxe::*;
 use pikaxe::ark::{Ark, ArkOffsetEntry};
+use pikaxe::io::BlockType;
 use pikaxe::scene::*;
 use itertools::Itertools;
 use log::debug;
-use std::{env::args, path::{Path, PathBuf}};
+use std::{collections::{BTreeMap, HashMap, HashSet}, env::args, path::{Path, PathBuf}};
 
 type ConsumeEventFn = fn(AppEvent);
 
@@ -14,6 +17,90 @@ pub struct MiloView {
     pub filter: String,
     pub class_filter: Option<String>,
     pub selected_entry: Option<String>,
+    pub selected_object: Option<String>, // Picked from viewport
+    pub scroll_to_selected: bool,
+    pub renaming_entry: Option<String>,
+    pub rename_text: String,
+    pub inspector_buffers: HashMap<String, String>, // Text being edited in inspector
+    pub tree_mode: bool, // Show scene graph instead of list
+    pub hidden_objects: HashSet<String>, // Toggled in tree, hides children too
+    pub hidden_meshes: HashSet<String>, // Resolved from hidden objects, used by viewport
+}
+
+pub enum ArkAction {
+    Extract(Vec<String>), // Entry paths
+    Replace(String), // Entry path
+    Add(String), // Dir path
+}
+
+#[derive(Default)]
+pub struct ArkView {
+    pub action: Option<ArkAction>, // Waiting on path input
+    pub action_path: String,
+    pub staged_files: BTreeMap<String, PathBuf>, // Ark path -> local file, written on save
+}
+
+#[derive(Default)]
+pub struct FileBrowserView {
+    pub open: bool,
+    pub current_dir: PathBuf,
+    pub path_text: String,
+    pub show_all_files: bool,
+    pub selected_file: Option<PathBuf>,
+    pub dirs: Vec<PathBuf>, // Cached listing of current dir
+    pub files: Vec<PathBuf>,
+}
+
+#[derive(Default)]
+pub struct SaveAsView {
+    pub open: bool,
+    pub path_text: String,
+    pub platform: Option<Platform>,
+    pub block_type: Option<BlockType>,
+}
+
+#[derive(Default)]
+pub struct ExportGltfView {
+    pub open: bool,
+    pub path_text: String,
+    pub selection_only: bool,
+    pub sidecar_textures: bool,
+}
+
+#[derive(Default)]
+pub struct ImportModelView {
+    pub open: bool,
+    pub path_text: String,
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum TextureChannel {
+    #[default]
+    RGBA,
+    RGB,
+    R,
+    G,
+    B,
+    A,
+}
+
+#[derive(Default)]
+pub struct TextureView {
+    pub tex_name: Option<String>,
+    pub followed_object: Option<String>, // Last milo selection followed
+    pub decoded: Option<DecodedTexture>,
+    pub handle: Option<egui::TextureHandle>, // Current mip + channel
+    pub needs_update: bool,
+    pub mip: usize,
+    pub channel: TextureChannel,
+    pub zoom: f32,
+    pub image_path: String,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CloseAction {
+    Close,
+    Exit,
 }
 
 #[derive(Default, Resource)]
@@ -22,12 +109,26 @@ pub struct AppState {
     pub root: Option<ArkDirNode>,
     pub system_info: Option<SystemInfo>,
     pub milo: Option<ObjectDir>,
+    pub milo_block_type: Option<BlockType>, // Original compression
     pub open_file_path: Option<PathBuf>,
+    pub open_ark_entry: Option<String>, // Milo opened from ark
     pub settings_path: PathBuf,
     pub show_options: bool,
+    pub show_history: bool,
+    pub show_references: bool,
+    pub history: EditHistory,
+    pub pending_close: Option<CloseAction>, // Waiting on unsaved changes prompt
+    pub close_after_save: Option<CloseAction>,
     pub pending_events: Vec<AppEvent>,
-    pub side_bar_tab_index: usize,
+    pub pending_file_events: Vec<AppFileEvent>,
+    pub side_bar_tab: SideBarTab,
+    pub ark_view: ArkView,
+    pub file_browser: FileBrowserView,
+    pub save_as: SaveAsView,
+    pub export_gltf: ExportGltfView,
+    pub import_model: ImportModelView,
     pub milo_view: MiloView,
+    pub texture_view: TextureView,
     pub vert_count: usize,
     pub face_count: usize,
 }
@@ -47,6 +148,130 @@ impl AppState {
     pub fn add_event(&mut self, ev: AppEvent) {
         self.pending_events.push(ev);
     }
+
+    /// Applies edit to open milo and records in history
+    pub fn execute_edit<T: Into<String>>(&mut self, description: T, command: EditCommand) {
+        self.execute_edit_merged(description, command, false);
+    }
+
+    /// Same as execute_edit but combines with last edit of same property
+    pub fn execute_edit_merged<T: Into<String>>(&mut self, description: T, command: EditCommand, merge: bool) {
+        let Some(milo) = self.milo.as_mut() else {
+            return;
+        };
+
+        if self.history.execute(milo, description.into(), command, merge) {
+            self.refresh_milo();
+        }
+    }
+
+    pub fn undo(&mut self) {
+        if self.milo.as_mut().is_some_and(|m| self.history.undo(m)) {
+            self.refresh_milo();
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if self.milo.as_mut().is_some_and(|m| self.history.redo(m)) {
+            self.refresh_milo();
+        }
+    }
+
+    pub fn jump_to_history(&mut self, depth: usize) {
+        if let Some(milo) = self.milo.as_mut() {
+            self.history.jump_to(milo, depth);
+            self.refresh_milo();
+        }
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.history.is_dirty() || !self.ark_view.staged_files.is_empty()
+    }
+
+    /// Closes file or app, prompts first if there are unsaved changes
+    pub fn request_close(&mut self, action: CloseAction) {
+        if self.is_dirty() {
+            self.pending_close = Some(action);
+            return;
+        }
+
+        self.add_event(action.into());
+    }
+
+    fn refresh_milo(&mut self) {
+        self.texture_view.needs_update = true;
+        self.add_event(AppEvent::SelectMiloEntry(self.milo_view.selected_entry.to_owned()));
+    }
+
+    /// Opens texture viewer tab for tex
+    pub fn view_texture(&mut self, tex_name: &str) {
+        self.texture_view.tex_name = Some(tex_name.to_owned());
+        self.texture_view.needs_update = true;
+        self.texture_view.mip = 0;
+        self.side_bar_tab = SideBarTab::Texture;
+    }
+
+    pub fn consume_file_events(&mut self, mut callback: impl FnMut(AppFileEvent)) {
+        while !self.pending_file_events.is_empty() {
+            callback(self.pending_file_events.remove(0));
+        }
+    }
+
+    pub fn add_file_event(&mut self, ev: AppFileEvent) {
+        self.pending_file_events.push(ev);
+    }
+}
+
+impl From<CloseAction> for AppEvent {
+    fn from(action: CloseAction) -> Self {
+        match action {
+            CloseAction::Close => AppEvent::Close,
+            CloseAction::Exit => AppEvent::Exit,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum SideBarTab {
+    #[default]
+    Ark,
+    Milo,
+    Texture,
+}
+
+impl SideBarTab {
+    pub const ALL: [SideBarTab; 3] = [SideBarTab::Ark, SideBarTab::Milo, SideBarTab::Texture];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SideBarTab::Ark => "Ark",
+            SideBarTab::Milo => "Milo",
+            SideBarTab::Texture => "Texture",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FileType {
+    Ark,
+    Milo,
+}
+
+/// Gets file type from last extension, ignoring platform suffix (i.e. ".milo_xbox")
+pub fn get_file_type(file_name: &str) -> Option<FileType> {
+    let (_, ext) = file_name.rsplit_once('.')?;
+
+    let ext = ext
+        .split('_')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    match ext.as_str() {
+        "hdr" => Some(FileType::Ark),
+        "milo" | "gh" | "rnd" => Some(FileType::Milo),
+        _ => None,
+    }
 }
 
 #[derive(Debug)]
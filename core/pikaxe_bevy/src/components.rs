@@ -1,3 +1,4 @@
+use crate::resources::{MiloObjectId, MiloSceneId};
 use bevy::prelude::*;
 use bevy::tasks::Task;
 
@@ -9,6 +10,7 @@ pub(crate) enum TextureType {
 
 #[derive(Component)]
 pub(crate) struct MiloAsyncTexture {
+    pub scene: MiloSceneId,
     pub tex_name: String,
     pub image_task: Task<Image>,
     pub mat_handles: Vec<(Handle<StandardMaterial>, TextureType)>,
@@ -19,7 +21,7 @@ pub struct MiloBandPlacer;
 
 #[derive(Component)]
 pub struct MiloObject {
-    pub id: u32,
+    pub id: MiloObjectId,
     pub name: String,
     pub dir: String,
 }
@@ -28,6 +30,13 @@ pub struct MiloObject {
 pub struct MiloMesh {
     pub verts: usize,
     pub faces: usize,
+    pub sphere_radius: f32,
+}
+
+#[derive(Component)]
+pub struct MiloText {
+    pub text: String,
+    pub font: String,
 }
 
 #[derive(Component)]
@@ -36,13 +45,55 @@ pub struct MiloBone;
 #[derive(Component)]
 pub struct MiloCam;
 
+/// Material created from milo mat, shared by meshes using it
+#[derive(Component)]
+pub struct MiloMaterial(pub Handle<StandardMaterial>);
+
+/// Decoded image of milo tex (only texs used by mats are decoded)
 #[derive(Component)]
-pub struct MiloCharHair;
+pub struct MiloTexImage(pub Handle<Image>);
+
+#[derive(Component)]
+pub struct MiloCharHair {
+    pub stiffness: f32,
+    pub inertia: f32,
+    pub gravity: f32,
+    pub friction: f32,
+    pub simulate: bool,
+    pub collides: Vec<String>,
+    pub strands: Vec<MiloHairStrand>,
+    pub(crate) bound: bool,
+    pub(crate) initialized: bool,
+}
+
+pub struct MiloHairStrand {
+    pub root: String,
+    pub points: Vec<MiloHairPoint>,
+}
+
+pub struct MiloHairPoint {
+    pub bone: String,
+    pub length: f32,
+    pub radius: f32,
+    pub pos: Vec3,
+    pub prev_pos: Vec3,
+    pub(crate) entity: Option<Entity>,
+    pub(crate) rest: Transform,
+}
+
+#[derive(Component)]
+pub struct MiloHairCollider {
+    pub radius: f32,
+}
 
 #[derive(Component)]
 pub struct MiloGroup {
-    pub objects: Vec<u32>,
+    pub objects: Vec<MiloObjectId>,
 }
 
+/// Parent used instead of root when object has no trans parent (i.e. character on band placer)
+#[derive(Component)]
+pub struct MiloAttachTo(pub Entity);
+
 #[derive(Component)]
 pub struct MiloRoot;
\ No newline at end of file
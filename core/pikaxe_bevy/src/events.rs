@@ -1,5 +1,6 @@
 use bevy::prelude::*;
 use bevy::ecs::system::EntityCommands;
+use crate::song::MiloSongNote;
 
 #[derive(Event)]
 pub struct ClearMiloScene;
@@ -23,4 +24,45 @@ pub struct LoadMiloSceneWithCommands(pub String, pub fn(&mut EntityCommands));
 pub struct LoadMiloSceneComplete(pub String);
 
 #[derive(Event)]
-pub struct UpdateMiloObjectParents;
\ No newline at end of file
+pub struct UpdateMiloObjectParents;
+
+#[derive(Event)]
+pub struct SetActiveMiloCam(pub String);
+
+/// Select entity (or clear selection with None)
+#[derive(Event)]
+pub struct SelectMiloEntity(pub Option<Entity>);
+
+#[derive(Event)]
+pub struct MiloEntitySelected(pub Option<Entity>);
+
+/// Load song midi (i.e. "songs/name/name.mid")
+#[derive(Event)]
+pub struct LoadMiloSong(pub String);
+
+#[derive(Event)]
+pub struct LoadMiloSongComplete(pub String);
+
+/// Fired when song time passes note start
+#[derive(Clone, Debug, Event)]
+pub struct MiloSongNoteEvent {
+    pub track: String,
+    pub note: MiloSongNote,
+    pub seconds: f64,
+    pub length_seconds: f64,
+}
+
+/// Fired when song time passes text event (i.e. "[lighting (verse)]")
+#[derive(Clone, Debug, Event)]
+pub struct MiloSongTextEvent {
+    pub track: String,
+    pub text: String,
+    pub seconds: f64,
+}
+
+/// Load venue milos (i.e. "arena") in dependency order, doesn't clear existing scenes
+#[derive(Event)]
+pub struct LoadVenue(pub String);
+
+#[derive(Event)]
+pub struct LoadVenueComplete(pub String);
@@ -0,0 +1,177 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use pikaxe::scene::{Font, Tex, Text};
+use std::collections::HashMap;
+
+// Text alignment flags (horizontal | vertical, left + top are default)
+const ALIGN_CENTER: u32 = 0x02;
+const ALIGN_RIGHT: u32 = 0x04;
+const ALIGN_MIDDLE: u32 = 0x20;
+const ALIGN_BOTTOM: u32 = 0x40;
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FontGlyph {
+    pub uv_min: Vec2,
+    pub uv_max: Vec2,
+    pub width: f32, // Relative to cell width
+}
+
+pub(crate) struct FontAtlas {
+    pub mat: String,
+    pub glyphs: HashMap<char, FontGlyph>,
+    pub cell_aspect: f32, // Cell width / height
+    pub base_kerning: f32,
+}
+
+impl FontAtlas {
+    pub fn from_font(font: &Font, tex: Option<&Tex>) -> Option<FontAtlas> {
+        let cell_size = Vec2::new(font.cell_size.x, font.cell_size.y);
+
+        if cell_size.x <= 0.0 || cell_size.y <= 0.0 {
+            log::warn!("Font \"{}\" has invalid cell size", &font.name);
+            return None;
+        }
+
+        // Glyphs are packed left to right, top to bottom
+        let columns = ((1.0 / cell_size.x).round() as usize).max(1);
+
+        let glyphs = font.chars
+            .iter()
+            .enumerate()
+            .map(|(i, ch)| {
+                let (col, row) = (i % columns, i / columns);
+                let uv_min = Vec2::new(col as f32 * cell_size.x, row as f32 * cell_size.y);
+
+                let width = match font.monospace {
+                    true => 1.0,
+                    _ => ch.width
+                };
+
+                (ch.character, FontGlyph {
+                    uv_min,
+                    uv_max: uv_min + Vec2::new(cell_size.x * width, cell_size.y),
+                    width,
+                })
+            })
+            .collect::<HashMap<_, _>>();
+
+        let cell_aspect = tex
+            .filter(|t| t.width > 0 && t.height > 0)
+            .map(|t| (cell_size.x * t.width as f32) / (cell_size.y * t.height as f32))
+            .unwrap_or(1.0);
+
+        Some(FontAtlas {
+            mat: font.mat.to_owned(),
+            glyphs,
+            cell_aspect,
+            base_kerning: font.base_kerning,
+        })
+    }
+
+    fn get_glyph(&self, c: char) -> Option<&FontGlyph> {
+        self.glyphs
+            .get(&c)
+            .or_else(|| self.glyphs.get(&c.to_ascii_uppercase()))
+    }
+
+    fn get_line_width(&self, line: &str, size: f32) -> f32 {
+        line
+            .chars()
+            .map(|c| self.get_char_advance(c, size))
+            .sum()
+    }
+
+    fn get_char_advance(&self, c: char, size: f32) -> f32 {
+        let cell_width = size * self.cell_aspect;
+
+        match self.get_glyph(c) {
+            Some(glyph) => (glyph.width + self.base_kerning) * cell_width,
+            None => cell_width * 0.5, // Treat unknown as space
+        }
+    }
+}
+
+/// Builds quads in milo XZ plane for text string
+pub(crate) fn build_text_mesh(text: &Text, atlas: &FontAtlas) -> Option<Mesh> {
+    if text.text.is_empty() || text.size <= 0.0 {
+        return None;
+    }
+
+    let size = text.size;
+    let line_height = size * text.leading.max(1.0);
+    let align = text.align as u32;
+
+    let lines = text.text
+        .split('\n')
+        .collect::<Vec<_>>();
+
+    let total_height = line_height * (lines.len() - 1) as f32 + size;
+
+    // Top edge of first line
+    let start_z = match align & 0xF0 {
+        ALIGN_MIDDLE => total_height / 2.0,
+        ALIGN_BOTTOM => total_height,
+        _ => 0.0, // Top
+    };
+
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    let mut indices = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        let line_width = atlas.get_line_width(line, size);
+        let top = start_z - (line_height * i as f32);
+
+        let mut x = match align & 0x0F {
+            ALIGN_CENTER => -line_width / 2.0,
+            ALIGN_RIGHT => -line_width,
+            _ => 0.0, // Left
+        };
+
+        for c in line.chars() {
+            let advance = atlas.get_char_advance(c, size);
+
+            let Some(glyph) = atlas.get_glyph(c) else {
+                x += advance;
+                continue;
+            };
+
+            let width = glyph.width * size * atlas.cell_aspect;
+            let idx = positions.len() as u32;
+
+            positions.extend([
+                [x, 0.0, top],
+                [x + width, 0.0, top],
+                [x + width, 0.0, top - size],
+                [x, 0.0, top - size],
+            ]);
+
+            uvs.extend([
+                [glyph.uv_min.x, glyph.uv_min.y],
+                [glyph.uv_max.x, glyph.uv_min.y],
+                [glyph.uv_max.x, glyph.uv_max.y],
+                [glyph.uv_min.x, glyph.uv_max.y],
+            ]);
+
+            indices.extend([idx, idx + 2, idx + 1, idx, idx + 3, idx + 2]);
+
+            x += advance;
+        }
+    }
+
+    if positions.is_empty() {
+        return None;
+    }
+
+    // Text faces -Y
+    let normals = vec![[0.0, -1.0, 0.0]; positions.len()];
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::RENDER_WORLD | RenderAssetUsages::MAIN_WORLD);
+    mesh.insert_indices(Indices::U32(indices));
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+
+    Some(mesh)
+}
\ No newline at end of file
@@ -0,0 +1,264 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use pikaxe::scene::CharHair;
+use std::collections::HashMap;
+
+const GRAVITY: f32 = 386.0; // Inches/s^2 (milo units)
+const MAX_STEP: f32 = 1.0 / 30.0;
+
+impl MiloCharHair {
+    pub(crate) fn from_char_hair(hair: &CharHair) -> MiloCharHair {
+        let strands = hair.strands
+            .iter()
+            .map(|s| MiloHairStrand {
+                root: s.root.to_owned(),
+                points: s.points
+                    .iter()
+                    .map(|p| MiloHairPoint {
+                        bone: p.bone.to_owned(),
+                        length: p.length,
+                        radius: p.radius,
+                        pos: Vec3::new(p.pos.x, p.pos.y, p.pos.z),
+                        prev_pos: Vec3::new(p.pos.x, p.pos.y, p.pos.z),
+                        entity: None,
+                        rest: Transform::IDENTITY,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        MiloCharHair {
+            stiffness: hair.stiffness.clamp(0.0, 1.0),
+            inertia: hair.inertia.clamp(0.0, 1.0),
+            gravity: hair.gravity,
+            friction: hair.friction.clamp(0.0, 1.0),
+            simulate: hair.simulate,
+            collides: hair.collides.to_owned(),
+            strands,
+            bound: false,
+            initialized: false,
+        }
+    }
+}
+
+pub(crate) fn bind_milo_hair_bones(
+    mut hair_query: Query<(&MiloObject, &mut MiloCharHair)>,
+    bone_query: Query<(Entity, &MiloObject, &Transform), Without<MiloCharHair>>,
+) {
+    for (hair_obj, mut hair) in hair_query.iter_mut() {
+        if hair.bound {
+            continue;
+        }
+
+        // Bone names are only unique within dir
+        let bones = bone_query
+            .iter()
+            .filter(|(_, obj, _)| obj.dir.eq(&hair_obj.dir))
+            .map(|(en, obj, trans)| (obj.name.as_str(), (en, *trans)))
+            .collect::<HashMap<_, _>>();
+
+        if bones.is_empty() {
+            continue;
+        }
+
+        for strand in hair.strands.iter_mut() {
+            for point in strand.points.iter_mut() {
+                if let Some((entity, trans)) = bones.get(point.bone.as_str()) {
+                    point.entity = Some(*entity);
+                    point.rest = *trans;
+                }
+            }
+
+            // Strand ends at first missing bone
+            let bound_count = strand.points
+                .iter()
+                .position(|p| p.entity.is_none())
+                .unwrap_or(strand.points.len());
+
+            if bound_count < strand.points.len() {
+                log::warn!("Can't find bone \"{}\" for hair strand \"{}\"", &strand.points[bound_count].bone, &strand.root);
+                strand.points.truncate(bound_count);
+            }
+        }
+
+        hair.bound = true;
+        log::debug!("Bound hair \"{}\" with {} strands", &hair_obj.name, hair.strands.len());
+    }
+}
+
+pub(crate) fn simulate_milo_hair(
+    time: Res<Time>,
+    world_settings: Res<MiloWorldSettings>,
+    mut hair_query: Query<(&MiloObject, &mut MiloCharHair)>,
+    mut trans_query: Query<&mut Transform>,
+    global_query: Query<(&GlobalTransform, Option<&ChildOf>)>,
+    collider_query: Query<(&MiloObject, &GlobalTransform, &MiloHairCollider)>,
+) {
+    let dt = time.delta_secs().min(MAX_STEP);
+    if dt <= 0.0 {
+        return;
+    }
+
+    for (hair_obj, mut hair) in hair_query.iter_mut() {
+        if !hair.bound || !hair.simulate {
+            continue;
+        }
+
+        // Collision spheres in world space (center, radius)
+        let spheres = collider_query
+            .iter()
+            .filter(|(obj, ..)| obj.dir.eq(&hair_obj.dir) && hair.collides.contains(&obj.name))
+            .map(|(_, global, collider)| {
+                let (scale, _, center) = global.to_scale_rotation_translation();
+                (center, collider.radius * scale.max_element())
+            })
+            .collect::<Vec<_>>();
+
+        let MiloCharHair { stiffness, inertia, gravity, friction, initialized, .. } = *hair;
+        let unit_scale = world_settings.unit_scale;
+
+        let down = match world_settings.axis {
+            MiloAxisConvention::YUp => Vec3::NEG_Y,
+            MiloAxisConvention::ZUp => Vec3::NEG_Z,
+        };
+
+        let accel = down * GRAVITY * gravity * unit_scale;
+
+        for strand in hair.strands.iter_mut() {
+            simulate_strand(strand, &mut trans_query, &global_query, &spheres, HairParams {
+                dt,
+                stiffness,
+                inertia,
+                friction,
+                accel,
+                unit_scale,
+                reset: !initialized,
+            });
+        }
+
+        hair.initialized = true;
+    }
+}
+
+struct HairParams {
+    dt: f32,
+    stiffness: f32,
+    inertia: f32,
+    friction: f32,
+    accel: Vec3,
+    unit_scale: f32, // Hair lengths are in milo units
+    reset: bool,
+}
+
+fn simulate_strand(
+    strand: &mut MiloHairStrand,
+    trans_query: &mut Query<&mut Transform>,
+    global_query: &Query<(&GlobalTransform, Option<&ChildOf>)>,
+    spheres: &[(Vec3, f32)],
+    params: HairParams,
+) {
+    let points = &mut strand.points;
+    if points.len() < 2 {
+        return;
+    }
+
+    // Root follows animated bone
+    let Some(root_entity) = points[0].entity else {
+        return;
+    };
+
+    let Ok((root_global, root_parent)) = global_query.get(root_entity) else {
+        return;
+    };
+
+    let root_pos = root_global.translation();
+
+    // Parent world rotation of first bone
+    let mut parent_rot = root_parent
+        .and_then(|p| global_query.get(p.parent()).ok())
+        .map(|(g, _)| g.to_scale_rotation_translation().1)
+        .unwrap_or(Quat::IDENTITY);
+
+    // Rest pose (relative to animated root)
+    let mut rest_positions = Vec::with_capacity(points.len());
+    let mut rest_rot = parent_rot * points[0].rest.rotation;
+    rest_positions.push(root_pos);
+
+    for i in 1..points.len() {
+        let prev = rest_positions[i - 1];
+        let offset = rest_rot * (points[i].rest.translation * params.unit_scale);
+
+        rest_positions.push(prev + offset);
+        rest_rot = rest_rot * points[i].rest.rotation;
+    }
+
+    if params.reset {
+        for (point, rest_pos) in points.iter_mut().zip(rest_positions.iter()) {
+            point.pos = *rest_pos;
+            point.prev_pos = *rest_pos;
+        }
+    }
+
+    // Verlet integration
+    points[0].prev_pos = points[0].pos;
+    points[0].pos = root_pos;
+
+    for i in 1..points.len() {
+        let point = &mut points[i];
+        let velocity = (point.pos - point.prev_pos) * (1.0 - params.friction) * params.inertia;
+
+        point.prev_pos = point.pos;
+        point.pos += velocity + params.accel * params.dt * params.dt;
+
+        // Pull towards rest pose
+        point.pos = point.pos.lerp(rest_positions[i], params.stiffness);
+    }
+
+    // Constraints
+    for i in 1..points.len() {
+        let prev_pos = points[i - 1].pos;
+        let length = match points[i - 1].length {
+            l if l > 0.0 => l * params.unit_scale,
+            _ => rest_positions[i].distance(rest_positions[i - 1]),
+        };
+
+        let point = &mut points[i];
+
+        for (center, radius) in spheres.iter() {
+            let min_dist = radius + (point.radius * params.unit_scale);
+            let diff = point.pos - *center;
+
+            if diff.length_squared() < min_dist * min_dist {
+                point.pos = *center + diff.normalize_or(Vec3::Y) * min_dist;
+            }
+        }
+
+        let dir = (point.pos - prev_pos).normalize_or(Vec3::NEG_Y);
+        point.pos = prev_pos + dir * length;
+    }
+
+    // Aim bones at next point
+    for i in 0..(points.len() - 1) {
+        let Some(entity) = points[i].entity else {
+            break;
+        };
+
+        let rest = points[i].rest;
+        let local_dir = points[i + 1].rest.translation.normalize_or_zero();
+
+        let world_rot = parent_rot * rest.rotation;
+        let world_dir = (points[i + 1].pos - points[i].pos).normalize_or_zero();
+
+        let new_world_rot = if local_dir == Vec3::ZERO || world_dir == Vec3::ZERO {
+            world_rot
+        } else {
+            Quat::from_rotation_arc((world_rot * local_dir).normalize(), world_dir) * world_rot
+        };
+
+        if let Ok(mut trans) = trans_query.get_mut(entity) {
+            trans.rotation = parent_rot.inverse() * new_world_rot;
+        }
+
+        parent_rot = new_world_rot;
+    }
+}
\ No newline at end of file
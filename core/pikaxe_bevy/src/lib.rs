@@ -1,11 +1,21 @@
 pub mod components;
 pub mod events;
+mod font;
+mod hair;
+pub mod picking;
 pub mod plugins;
+pub mod props;
 pub mod resources;
+pub mod song;
+pub mod venue;
 
 pub mod prelude {
     pub use super::components::*;
     pub use super::events::*;
+    pub use super::picking::*;
     pub use super::plugins::*;
+    pub use super::props::*;
     pub use super::resources::*;
+    pub use super::song::*;
+    pub use super::venue::*;
 }
\ No newline at end of file
@@ -0,0 +1,170 @@
+use crate::prelude::*;
+use bevy::picking::mesh_picking::MeshPickingPlugin;
+use bevy::prelude::*;
+
+const DRAG_THRESHOLD: f32 = 4.0; // Pixels
+
+/// Ray picking for milo meshes, adds mesh picking backend if not already added
+#[derive(Default)]
+pub struct MiloPickingPlugin;
+
+impl Plugin for MiloPickingPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<MeshPickingPlugin>() {
+            app.add_plugins(MeshPickingPlugin);
+        }
+
+        app.add_event::<SelectMiloEntity>();
+        app.add_event::<MiloEntitySelected>();
+
+        app.init_resource::<MiloPickingSettings>();
+        app.init_resource::<MiloPickingState>();
+
+        app.add_observer(reset_milo_pick_drag);
+        app.add_observer(update_milo_pick_drag);
+        app.add_observer(pick_milo_entity);
+
+        app.add_systems(Update, process_select_milo_entity_events);
+    }
+}
+
+#[derive(Resource)]
+pub struct MiloPickingSettings {
+    pub enabled: bool, // i.e. disable when pointer is over gui
+    pub highlight_color: Color,
+}
+
+impl Default for MiloPickingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            highlight_color: Color::srgb(1.0, 0.6, 0.1),
+        }
+    }
+}
+
+/// Entity that can be selected, clicks on children select nearest pickable ancestor
+#[derive(Component, Default)]
+pub struct MiloPickable;
+
+#[derive(Component)]
+pub struct MiloSelected {
+    original_mat: Option<Handle<StandardMaterial>>,
+}
+
+#[derive(Default, Resource)]
+struct MiloPickingState {
+    dragged: bool, // Clicks after camera drags are ignored
+}
+
+// Observers are global so events keep bubbling for host app entities
+
+fn reset_milo_pick_drag(
+    _trigger: Trigger<Pointer<Pressed>>,
+    mut pick_state: ResMut<MiloPickingState>,
+) {
+    pick_state.dragged = false;
+}
+
+fn update_milo_pick_drag(
+    trigger: Trigger<Pointer<Drag>>,
+    mut pick_state: ResMut<MiloPickingState>,
+) {
+    if trigger.event().distance.length() > DRAG_THRESHOLD {
+        pick_state.dragged = true;
+    }
+}
+
+fn pick_milo_entity(
+    trigger: Trigger<Pointer<Click>>,
+    settings: Res<MiloPickingSettings>,
+    pick_state: Res<MiloPickingState>,
+    pickable_query: Query<(), With<MiloPickable>>,
+    parent_query: Query<&ChildOf>,
+    mut select_events_writer: EventWriter<SelectMiloEntity>,
+) {
+    // Only handle original target, ancestors are checked below
+    if trigger.target() != trigger.event().target {
+        return;
+    }
+
+    if !settings.enabled || pick_state.dragged || trigger.event().button != PointerButton::Primary {
+        return;
+    }
+
+    // Clicking empty space (i.e. window) clears selection
+    let picked_entity = std::iter::once(trigger.target())
+        .chain(parent_query.iter_ancestors(trigger.target()))
+        .find(|en| pickable_query.contains(*en));
+
+    select_events_writer.write(SelectMiloEntity(picked_entity));
+}
+
+fn process_select_milo_entity_events(
+    mut commands: Commands,
+    mut select_events_reader: EventReader<SelectMiloEntity>,
+    mut selected_events_writer: EventWriter<MiloEntitySelected>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    settings: Res<MiloPickingSettings>,
+    mut mat_query: Query<&mut MeshMaterial3d<StandardMaterial>>,
+    selected_query: Query<(Entity, &MiloSelected)>,
+    name_query: Query<&Name>,
+) {
+    // Only last event matters
+    let Some(SelectMiloEntity(entity)) = select_events_reader.read().last() else {
+        return;
+    };
+
+    if selected_query.iter().any(|(en, _)| entity.eq(&Some(en))) {
+        return;
+    }
+
+    // Restore original materials
+    for (selected_entity, selected) in selected_query.iter() {
+        if let (Ok(mut mesh_mat), Some(original_mat)) = (mat_query.get_mut(selected_entity), selected.original_mat.as_ref()) {
+            mesh_mat.0 = original_mat.clone();
+        }
+
+        commands
+            .entity(selected_entity)
+            .try_remove::<MiloSelected>(); // Might be despawned
+    }
+
+    if let Some(entity) = entity {
+        // Tint copy of material so shared materials aren't affected
+        let original_mat = match mat_query.get_mut(*entity) {
+            Ok(mut mesh_mat) => {
+                let original_mat = mesh_mat.0.clone();
+
+                if let Some(mat) = materials.get(&original_mat) {
+                    let mut highlight_mat = mat.clone();
+                    highlight_mat.base_color = mix_colors(mat.base_color, settings.highlight_color);
+                    highlight_mat.emissive = settings.highlight_color.to_linear() * 0.25;
+
+                    mesh_mat.0 = materials.add(highlight_mat);
+                }
+
+                Some(original_mat)
+            },
+            _ => None
+        };
+
+        commands
+            .entity(*entity)
+            .try_insert(MiloSelected { original_mat });
+
+        match name_query.get(*entity) {
+            Ok(name) => log::debug!("Selected \"{}\"", name),
+            _ => log::debug!("Selected {}", entity),
+        }
+    }
+
+    selected_events_writer.write(MiloEntitySelected(*entity));
+}
+
+fn mix_colors(base: Color, highlight: Color) -> Color {
+    let base = base.to_linear();
+    let mixed = base.mix(&highlight.to_linear(), 0.5);
+
+    Color::LinearRgba(mixed.with_alpha(base.alpha.max(0.5)))
+}
@@ -1,64 +1,114 @@
+use crate::font::*;
+use crate::hair::*;
 use crate::prelude::*;
+use crate::props::*;
+use crate::song::*;
+use crate::venue::*;
 use bevy::image::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor};
 use bevy::prelude::*;
 use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
-use bevy::tasks::AsyncComputeTaskPool;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use bevy::transform::TransformSystem;
 use std::collections::{HashMap, HashSet};
 use futures_lite::future;
-use pikaxe::ark::Ark;
-use pikaxe::scene::{Blend, Matrix, MiloObject as MObject, Object, ObjectDir, Sphere as MiloSphere, Trans, RndMesh, ZMode};
+use pikaxe::scene::{Blend, MatObject, Matrix, MeshObject, MiloObject as MObject, Object, ObjectDir, Sphere as MiloSphere, Tex, Trans, RndMesh, ZMode};
 use pikaxe::texture::Bitmap;
-use pikaxe::Platform;
+use pikaxe::{Platform, SystemInfo};
 use std::path::PathBuf;
 
 #[derive(Default)]
 pub struct MiloPlugin {
     pub ark_path: Option<PathBuf>,
+    pub sources: Vec<MiloSourcePath>, // Patch arks + loose dirs, applied on top of base ark
     pub default_outfit: Option<String>,
+    pub world_settings: MiloWorldSettings,
 }
 
 impl Plugin for MiloPlugin {
     fn build(&self, app: &mut App) {
-        // Open ark
-        let state = MiloState {
-            ark: self.ark_path
-                .as_ref()
-                .map(|p| Ark::from_path(p).expect("Can't open ark file")),
-            ..Default::default()
-        };
+        let mut state = MiloState::default();
+
+        // Open base ark + overrides
+        let source_paths = self.ark_path
+            .iter()
+            .map(|p| MiloSourcePath::Ark(p.to_owned()))
+            .chain(self.sources.iter().cloned());
+
+        for source in source_paths.flat_map(|p| MiloSource::from_source_path(&p)) {
+            state.add_source(source);
+        }
 
         app.add_event::<ClearMiloScene>();
         app.add_event::<LoadMiloScene>();
         app.add_event::<LoadMiloSceneWithCommands>();
         app.add_event::<LoadMiloSceneComplete>();
         app.add_event::<UpdateMiloObjectParents>();
+        app.add_event::<SetActiveMiloCam>();
+        app.add_event::<LoadMiloSong>();
+        app.add_event::<LoadMiloSongComplete>();
+        app.add_event::<MiloSongNoteEvent>();
+        app.add_event::<MiloSongTextEvent>();
+        app.add_event::<LoadVenue>();
+        app.add_event::<LoadVenueComplete>();
 
         app.insert_resource(state);
+        app.insert_resource(self.world_settings);
+        app.init_resource::<MiloAssetCache>();
+        app.init_resource::<MiloSongTime>();
+        app.init_resource::<MiloVenueBand>();
+        app.init_resource::<MiloVenueState>();
 
         app.add_systems(Startup, init_world);
 
         app.add_systems(Update, (
+            process_clear_milo_scene_events,
             process_milo_scene_events,
             update_milo_object_parents.after(process_milo_scene_events)
         ).chain());
 
-        app.add_systems(Update, process_milo_async_textures);
+        app.add_systems(Update, process_milo_async_textures.after(process_milo_scene_events));
+
+        app.add_systems(Update, (
+            process_set_active_milo_cam,
+            update_milo_cam_projections
+        ).chain());
+
+        app.add_systems(Update, (
+            process_load_venue_events.before(process_milo_scene_events),
+            place_venue_band.after(update_milo_object_parents)
+        ));
+
+        app.add_systems(Update, (
+            process_load_milo_song_events,
+            update_milo_song_time
+        ).chain());
+
+        app.add_systems(Update, bind_milo_hair_bones.after(update_milo_object_parents));
+        app.add_systems(PostUpdate, simulate_milo_hair.before(TransformSystem::TransformPropagate));
+
+        // Editable props (i.e. for inspector)
+        app.register_type::<MiloTransProps>();
+        app.register_type::<MiloMeshProps>();
+        app.register_type::<MiloMatProps>();
+        app.register_type::<MiloCamProps>();
+        app.register_type::<MiloTexProps>();
+        app.register_type::<MiloGroupProps>();
+
+        app.add_systems(Update, (
+            sync_milo_trans_props,
+            sync_milo_cam_props,
+            sync_milo_mat_props,
+            sync_milo_mesh_props
+        ).after(update_milo_object_parents));
     }
 }
 
 fn init_world(
     mut commands: Commands,
+    world_settings: Res<MiloWorldSettings>,
 ) {
     // Translate to bevy coordinate system
-    let trans_mat = Mat4::from_cols_array(&[
-        -1.0,  0.0,  0.0, 0.0,
-        0.0,  0.0,  1.0, 0.0,
-        0.0,  1.0,  0.0, 0.0,
-        0.0,  0.0,  0.0, 1.0,
-    ]);
-
-    //let scale_mat = Mat4::from_scale(Vec3::new(0.1, 0.1, 0.1));
-    let trans = Transform::from_matrix(trans_mat);
+    let trans = Transform::from_matrix(world_settings.get_world_matrix());
 
     commands
         .spawn(Name::new("Root"))
@@ -70,6 +120,33 @@ fn init_world(
         .insert(MiloRoot);
 }
 
+fn process_clear_milo_scene_events(
+    mut commands: Commands,
+    mut clear_events_reader: EventReader<ClearMiloScene>,
+    mut state: ResMut<MiloState>,
+    mut asset_cache: ResMut<MiloAssetCache>,
+    milo_objects_query: Query<Entity, With<MiloObject>>,
+    async_textures_query: Query<Entity, With<MiloAsyncTexture>>,
+) {
+    if clear_events_reader.is_empty() {
+        return;
+    }
+
+    clear_events_reader.clear();
+
+    // Pending textures would otherwise update materials from old scenes
+    for entity in milo_objects_query.iter().chain(async_textures_query.iter()) {
+        commands
+            .entity(entity)
+            .try_despawn();
+    }
+
+    state.clear_scenes();
+    asset_cache.clear();
+
+    log::debug!("Cleared milo scenes");
+}
+
 // TODO: Move to separate file?
 fn process_milo_scene_events(
     mut commands: Commands,
@@ -78,14 +155,11 @@ fn process_milo_scene_events(
     mut state: ResMut<MiloState>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut asset_cache: ResMut<MiloAssetCache>,
     mut scene_events_writer: EventWriter<LoadMiloSceneComplete>,
     mut update_parents_events_writer: EventWriter<UpdateMiloObjectParents>,
     root_query: Query<Entity, With<MiloRoot>>,
 ) {
-    /*for e in state.ark.as_ref().unwrap().entries.iter() {
-        log::debug!("{}", &e.path);
-    }*/
-
     let thread_pool = AsyncComputeTaskPool::get();
     let root_entity = root_query.single().unwrap(); // TODO: Handle safely
 
@@ -101,22 +175,32 @@ fn process_milo_scene_events(
 
     // TODO: Check if path ends in .milo
     for (milo_path, callback) in scene_events {
-        let start_idx = state.objects.len();
         log::debug!("Loading Scene: \"{}\"", milo_path);
 
-        let (sys_info, mut milo) = state.open_milo(&milo_path).unwrap();
+        let Some((sys_info, milo)) = state.open_milo(&milo_path) else {
+            log::error!("Can't open scene: \"{}\"", milo_path);
+            continue;
+        };
+
+        let scene_id = state.insert_scene(MiloScene::new(milo_path.to_owned(), sys_info, milo));
+
+        let scene = state.get_scene(scene_id).unwrap(); // Shouldn't fail
+        let (sys_info, milo) = (&scene.system_info, scene.get_dir());
 
-        let obj_dir_name = match &milo {
+        let obj_dir_name = match milo {
             ObjectDir::ObjectDir(dir) => &dir.name
         };
 
         //let mut texture_map = HashMap::new(); // name -> tex future
 
-        let (milo_textures, milo_materials, milo_meshes) = milo
+        let (milo_textures, milo_materials, milo_meshes, milo_fonts) = milo
             .get_entries()
             .iter()
-            .fold((HashMap::new(), HashMap::new(), HashMap::new()), |(mut tx, mut mt, mut ms), e| {
+            .fold((HashMap::new(), HashMap::new(), HashMap::new(), HashMap::new()), |(mut tx, mut mt, mut ms, mut ft), e| {
                 match e {
+                    Object::Font(font) => {
+                        ft.insert(font.get_name(), font);
+                    },
                     Object::Mat(mat) => {
                         mt.insert(mat.get_name(), mat);
                     },
@@ -129,20 +213,34 @@ fn process_milo_scene_events(
                     _ => {}
                 }
 
-                (tx, mt, ms)
+                (tx, mt, ms, ft)
             });
 
-        // Mesh -> mat
+        // Font -> mat -> tex (for glyph metrics)
+        let font_atlases = milo_fonts
+            .iter()
+            .flat_map(|(name, font)| {
+                let tex = milo_materials
+                    .get(&font.mat)
+                    .and_then(|m| milo_textures.get(&m.diffuse_tex))
+                    .map(|t| *t);
+
+                FontAtlas::from_font(font, tex).map(|a| (name.as_str(), a))
+            })
+            .collect::<HashMap<_, _>>();
+
+        // Mesh -> mat, font -> mat
         let materials_to_load = milo_meshes
             .values()
             .map(|m| &m.mat)
+            .chain(milo_fonts.values().map(|f| &f.mat))
             .collect::<HashSet<_>>()
             .iter()
             .flat_map(|m| milo_materials.get(m).as_ref().map(|m| *m))
             .collect::<Vec<_>>();
 
-        // Mesh -> mat -> tex (create tasks)
-        let mut textures_to_load = materials_to_load
+        // Mesh -> mat -> tex (only decode textures in use)
+        let textures_in_use = materials_to_load
             .iter()
             .fold(HashSet::new(), |mut acc, m| {
                 acc.insert(&m.diffuse_tex);
@@ -150,103 +248,21 @@ fn process_milo_scene_events(
                 acc.insert(&m.emissive_map);
 
                 acc
-            })
-            .into_iter()
-            .flat_map(|t| milo_textures.get(t).map(|t| *t))
-            .filter(|t| t.bitmap.is_some())
-            .map(|tex| {
-                let sys_info = sys_info.clone();
-
-                /*let name = tex
-                    .get_name()
-                    .to_owned();*/
-
-                let bitmap = tex.bitmap
-                    .as_ref()
-                    .unwrap()
-                    .clone();
-
-                let task = thread_pool.spawn(async move {
-                    // Decode texture
-                    let (decoded, bpp, format) = match (&sys_info.platform, bitmap.encoding) {
-                        (Platform::X360 | Platform::PS3, enc @ (8 | 24 | 32)) => {
-                            let mut data = bitmap.raw_data;
-
-                            if sys_info.platform.eq(&Platform::X360) {
-                                // Swap bytes
-                                for ab in data.chunks_mut(2) {
-                                    let tmp = ab[0];
-
-                                    ab[0] = ab[1];
-                                    ab[1] = tmp;
-                                }
-                            }
-
-                            let format = match enc {
-                                24 => TextureFormat::Bc3RgbaUnormSrgb, // DXT5
-                                32 => TextureFormat::Bc5RgUnorm,       // ATI2
-                                _  => TextureFormat::Bc1RgbaUnormSrgb, // DXT1
-                            };
-
-                            (data, bitmap.bpp as usize, format)
-                        },
-                        _ => {
-                            let data = bitmap.unpack_rgba(&sys_info)
-                                .expect("Can't decode \"{name}\" texture");
-
-                            (data, 32, TextureFormat::Rgba8UnormSrgb)
-                        }
-                    };
-
-                    let Bitmap { width, height, mip_maps, .. } = bitmap;
-
-                    let tex_size = ((width as usize) * (height as usize) * bpp) / 8;
-                    let use_mips = false; // TODO: Always support mips?
-
-                    let img_slice = if use_mips {
-                        &decoded
-                    } else {
-                        &decoded[..tex_size]
-                    };
-
-                    let image_new_fn = match format {
-                        TextureFormat::Rgba8UnormSrgb => image_new_fill, // Use fill method for older textures
-                        _ => image_new,
-                    };
-
-                    let mut texture = /*Image::new_fill*/ image_new_fn(
-                        Extent3d {
-                            width: width.into(),
-                            height: height.into(),
-                            depth_or_array_layers: 1,
-                        },
-                        TextureDimension::D2,
-                        img_slice,
-                        format
-                    );
-
-                    // Update texture wrap mode
-                    texture.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
-                        address_mode_u: ImageAddressMode::Repeat,
-                        address_mode_v: ImageAddressMode::Repeat,
-                        anisotropy_clamp: 1, // 16,
-                        ..ImageSamplerDescriptor::default()
-                    });
-
-                    // Set mipmap level
-                    if use_mips {
-                        texture.texture_descriptor.mip_level_count = mip_maps as u32 + 1;
-                    }
+            });
 
-                    texture
-                });
+        // Tex name -> materials to update once decoded
+        let mut tex_bindings = HashMap::new();
 
-                // Tex name, (task, Vec<(mat handle, tex type)>)
-                (tex.get_name(), (task, Vec::new()))
+        // Mat name -> material
+        let mat_handles = milo_materials
+            .iter()
+            .map(|(name, mat)| {
+                let handle = get_or_add_material(&mut asset_cache, &mut materials, &mut tex_bindings, scene_id, Some(*mat));
+                (name.as_str(), handle)
             })
             .collect::<HashMap<_, _>>();
 
-        for (i, obj) in milo.get_entries().iter().enumerate() {
+        for (obj_id, obj) in state.get_objects(scene_id) {
             match obj {
                 Object::BandPlacer(band_placer) => {
                     let mat = map_matrix(band_placer.get_local_xfm());
@@ -255,10 +271,11 @@ fn process_milo_scene_events(
                         .spawn(Name::new(band_placer.name.to_owned()))
                         .insert((Transform::from_matrix(mat), Visibility::Visible))
                         .insert(MiloObject {
-                            id: (start_idx + i) as u32,
+                            id: obj_id,
                             name: band_placer.name.to_owned(),
                             dir: obj_dir_name.to_owned(),
                         })
+                        .insert(MiloTransProps::from_trans(band_placer))
                         .insert(MiloBandPlacer)
                         .id();
 
@@ -291,15 +308,21 @@ fn process_milo_scene_events(
                                 }
                             ),
                             Transform::from_matrix(
-                                map_matrix(cam.get_local_xfm())
-                            ), //.looking_at(Vec3::ZERO, Vec3::Z),
+                                map_matrix(cam.get_local_xfm()) * map_cam_basis()
+                            ),
                             Visibility::Visible
                         ))
                         .insert(MiloObject {
-                            id: (start_idx + i) as u32,
+                            id: obj_id,
                             name: cam.name.to_owned(),
                             dir: obj_dir_name.to_owned(),
                         })
+                        .insert(MiloTransProps::from_trans(cam))
+                        .insert(MiloCamProps {
+                            y_fov: cam.y_fov,
+                            near_plane: cam.near_plane,
+                            far_plane: cam.far_plane,
+                        })
                         .insert(MiloCam)
                         .id();
 
@@ -314,6 +337,57 @@ fn process_milo_scene_events(
 
                     log::info!("Loaded cam: {}", cam.get_name());
                 },
+                Object::CharCollide(collide) => {
+                    let mat = map_matrix(collide.get_local_xfm());
+
+                    let collide_entity = commands
+                        .spawn(Name::new(collide.name.to_owned()))
+                        .insert((Transform::from_matrix(mat), Visibility::Visible))
+                        .insert(MiloObject {
+                            id: obj_id,
+                            name: collide.name.to_owned(),
+                            dir: obj_dir_name.to_owned(),
+                        })
+                        .insert(MiloTransProps::from_trans(collide))
+                        .insert(MiloHairCollider {
+                            radius: collide.radius,
+                        })
+                        .id();
+
+                    if let Some(callback) = callback {
+                        let mut entity_command = commands.entity(collide_entity);
+                        callback(&mut entity_command);
+                    }
+
+                    commands
+                        .entity(root_entity)
+                        .add_child(collide_entity);
+
+                    log::info!("Loaded char collide: {}", collide.get_name());
+                },
+                Object::CharHair(hair) => {
+                    let hair_entity = commands
+                        .spawn(Name::new(hair.name.to_owned()))
+                        .insert((Transform::default(), Visibility::Visible))
+                        .insert(MiloObject {
+                            id: obj_id,
+                            name: hair.name.to_owned(),
+                            dir: obj_dir_name.to_owned(),
+                        })
+                        .insert(MiloCharHair::from_char_hair(hair))
+                        .id();
+
+                    if let Some(callback) = callback {
+                        let mut entity_command = commands.entity(hair_entity);
+                        callback(&mut entity_command);
+                    }
+
+                    commands
+                        .entity(root_entity)
+                        .add_child(hair_entity);
+
+                    log::info!("Loaded char hair: {} ({} strands)", hair.get_name(), hair.strands.len());
+                },
                 Object::Group(group) => {
                     let mat = map_matrix(group.get_local_xfm());
 
@@ -321,10 +395,12 @@ fn process_milo_scene_events(
                         .spawn(Name::new(group.name.to_owned()))
                         .insert((Transform::from_matrix(mat), Visibility::Visible))
                         .insert(MiloObject {
-                            id: (start_idx + i) as u32,
+                            id: obj_id,
                             name: group.name.to_owned(),
                             dir: obj_dir_name.to_owned(),
                         })
+                        .insert(MiloTransProps::from_trans(group))
+                        .insert(MiloGroupProps::from(group))
                         .id();
 
                     if let Some(callback) = callback {
@@ -338,6 +414,30 @@ fn process_milo_scene_events(
 
                     log::info!("Loaded group: {}", group.get_name());
                 },
+                Object::Mat(mat) => {
+                    let Some(mat_handle) = mat_handles.get(mat.get_name().as_str()) else {
+                        continue;
+                    };
+
+                    // Not spatial, only holds props
+                    let mat_entity = commands
+                        .spawn(Name::new(mat.name.to_owned()))
+                        .insert(MiloObject {
+                            id: obj_id,
+                            name: mat.name.to_owned(),
+                            dir: obj_dir_name.to_owned(),
+                        })
+                        .insert(MiloMatProps::from(mat))
+                        .insert(MiloMaterial(mat_handle.clone()))
+                        .id();
+
+                    if let Some(callback) = callback {
+                        let mut entity_command = commands.entity(mat_entity);
+                        callback(&mut entity_command);
+                    }
+
+                    log::info!("Loaded mat: {}", mat.get_name());
+                },
                 Object::Mesh(mesh) => {
                     // Ignore meshes without geometry (used mostly in GH1)
                     if mesh.vertices.is_empty() || mesh.name.starts_with("shadow") {
@@ -346,128 +446,66 @@ fn process_milo_scene_events(
 
                     let mat = map_matrix(mesh.get_local_xfm());
 
-                    let mut bevy_mesh = Mesh::new(bevy::render::render_resource::PrimitiveTopology::TriangleList, bevy::render::render_asset::RenderAssetUsages::RENDER_WORLD | bevy::render::render_asset::RenderAssetUsages::MAIN_WORLD);
-
-                    let vert_count = mesh.get_vertices().len();
-
-                    let mut positions = vec![Default::default(); vert_count];
-                    let mut normals = vec![Default::default(); vert_count];
-                    let mut tangents = vec![Default::default(); vert_count];
-                    let mut uvs = vec![Default::default(); vert_count];
-
-                    for (i, vert) in mesh.get_vertices().iter().enumerate() {
-                        positions[i] = [vert.pos.x, vert.pos.y, vert.pos.z];
+                    // Re-use geometry if already loaded
+                    let mesh_handle = asset_cache.meshes
+                        .entry(get_mesh_key(mesh))
+                        .or_insert_with(|| meshes.add(map_mesh(mesh)))
+                        .clone();
 
-                        // TODO: Figure out normals/tangents
-                        //normals.push([vert.normals.x, vert.normals.y, vert.normals.z]);
-                        normals[i] = [1.0, 1.0, 1.0];
-                        tangents[i] = [0.0, 0.0, 0.0, 1.0];
-
-                        uvs[i] = [vert.uv.u, vert.uv.v];
-                    }
-
-                    let indices = bevy::render::mesh::Indices::U16(
-                        mesh.faces.iter().flat_map(|f| *f).collect()
-                    );
-
-                    bevy_mesh.insert_indices(indices);
-                    bevy_mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
-                    bevy_mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
-                    bevy_mesh.insert_attribute(Mesh::ATTRIBUTE_TANGENT, tangents);
-                    bevy_mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
-
-                    let milo_mat = milo_materials.get(&mesh.mat);
-
-                    let bevy_mat = match milo_mat {
-                        Some(mat) => StandardMaterial {
-                            //alpha_mode: AlphaMode::Blend,
-                            alpha_mode: match (mat.blend, mat.z_mode) {
-                                (Blend::kBlendSrcAlpha, ZMode::kZModeDisable) => AlphaMode::Blend,
-                                _ => AlphaMode::Opaque
-                            },
-                            base_color: Color::srgba(
-                                mat.color.r,
-                                mat.color.g,
-                                mat.color.b,
-                                mat.alpha,
-                            ),
-                            double_sided: true,
-                            unlit: true,
-                            base_color_texture: None,
-                            normal_map_texture: None,
-                            emissive_texture: None,
-                            //roughness: 0.8, // TODO: Bevy 0.6 migration
-                            /*base_color_texture: get_texture(&mut loader, &mat.diffuse_tex, system_info)
-                                .and_then(map_texture)
-                                .and_then(|t| Some(bevy_textures.add(t))),
-                            normal_map: get_texture(&mut loader, &mat.norm_detail_map, system_info)
-                                .and_then(map_texture)
-                                .and_then(|t| Some(bevy_textures.add(t))),
-                            emissive_texture: get_texture(&mut loader, &mat.emissive_map, system_info)
-                                .and_then(map_texture)
-                                .and_then(|t| Some(bevy_textures.add(t))),*/
-                            ..Default::default()
-                        },
-                        None => StandardMaterial {
-                            base_color: Color::srgb(0.3, 0.5, 0.3),
-                            double_sided: true,
-                            unlit: false,
-                            ..Default::default()
-                        },
+                    let mat_handle = match mat_handles.get(mesh.mat.as_str()) {
+                        Some(handle) => handle.clone(),
+                        None => get_or_add_material(&mut asset_cache, &mut materials, &mut tex_bindings, scene_id, None),
                     };
 
-                    let mat_handle = materials.add(bevy_mat);
-
-                    let textures = [
-                        (milo_mat.map(|mat| &mat.diffuse_tex), TextureType::Diffuse),
-                        (milo_mat.map(|mat| &mat.normal_map), TextureType::Normal),
-                        (milo_mat.map(|mat| &mat.emissive_map), TextureType::Emissive),
-                    ];
-
-                    for (tex, typ) in textures.into_iter() {
-                        let Some((_, mats)) = tex.and_then(|t| textures_to_load.get_mut(t)) else {
-                            continue
-                        };
-
-                        mats.push((mat_handle.clone(), typ));
-                    }
-
                     // Add mesh
                     let mesh_entity = commands
                         .spawn(Name::new(mesh.name.to_owned()))
                         .insert((
-                            Mesh3d(meshes.add(bevy_mesh)),
+                            Mesh3d(mesh_handle),
                             MeshMaterial3d(mat_handle),
                             Transform::from_matrix(mat),
                             Visibility::Visible
                         ))
                         .insert(MiloObject {
-                            id: (start_idx + i) as u32,
+                            id: obj_id,
                             name: mesh.name.to_owned(),
                             dir: obj_dir_name.to_owned(),
                         })
+                        .insert(MiloTransProps::from_trans(mesh))
+                        .insert(MiloMeshProps::from(mesh))
                         .insert(MiloMesh {
                             verts: mesh.vertices.len(),
-                            faces: mesh.faces.len()
+                            faces: mesh.faces.len(),
+                            sphere_radius: mesh.sphere.r,
                         })
+                        .insert(MiloPickable)
                         .id();
 
                     if mesh.sphere.r > 0.0 {
-                        // Create sphere (TODO: Spawn from shared mesh)
+                        // Create sphere from shared mesh
                         let MiloSphere { x, y, z, r } = &mesh.sphere;
 
                         log::debug!("Adding sphere to {} with radius {}", &mesh.name, *r);
 
-                        let sphere_entity = commands
-                            .spawn((
-                                Mesh3d(meshes.add(Sphere::new(*r).mesh())),
-                                MeshMaterial3d(materials.add({
-                                    let mut mat: StandardMaterial = Color::srgb(0.9, 0.9, 0.9).into();
+                        let sphere_mesh = asset_cache.spheres
+                            .entry(r.to_bits())
+                            .or_insert_with(|| meshes.add(Sphere::new(*r).mesh()))
+                            .clone();
+
+                        let sphere_mat = asset_cache.sphere_material
+                            .get_or_insert_with(|| materials.add({
+                                let mut mat: StandardMaterial = Color::srgb(0.9, 0.9, 0.9).into();
 
-                                    mat.unlit = true;
+                                mat.unlit = true;
 
-                                    mat
-                                })),
+                                mat
+                            }))
+                            .clone();
+
+                        let sphere_entity = commands
+                            .spawn((
+                                Mesh3d(sphere_mesh),
+                                MeshMaterial3d(sphere_mat),
                                 Transform::from_xyz(*x, *y, *z),
                                 Visibility::Visible
                             ))
@@ -499,6 +537,90 @@ fn process_milo_scene_events(
 
                     log::info!("Loaded mesh: {}", mesh.get_name());
                 },
+                Object::Text(text) => {
+                    let Some(atlas) = font_atlases.get(text.font.as_str()) else {
+                        log::warn!("Can't find font \"{}\" for text \"{}\"", &text.font, text.get_name());
+                        continue;
+                    };
+
+                    let Some(text_mesh) = build_text_mesh(text, atlas) else {
+                        continue;
+                    };
+
+                    let mat = map_matrix(text.get_local_xfm());
+
+                    let mat_handle = materials.add(StandardMaterial {
+                        alpha_mode: AlphaMode::Blend,
+                        base_color: Color::srgba(
+                            text.color.r,
+                            text.color.g,
+                            text.color.b,
+                            text.color.a,
+                        ),
+                        double_sided: true,
+                        unlit: true,
+                        ..Default::default()
+                    });
+
+                    // Use font texture
+                    if let Some(font_mat) = milo_materials.get(&atlas.mat) {
+                        tex_bindings
+                            .entry(&font_mat.diffuse_tex)
+                            .or_default()
+                            .push((mat_handle.clone(), TextureType::Diffuse));
+                    }
+
+                    let text_entity = commands
+                        .spawn(Name::new(text.name.to_owned()))
+                        .insert((
+                            Mesh3d(meshes.add(text_mesh)),
+                            MeshMaterial3d(mat_handle),
+                            Transform::from_matrix(mat),
+                            Visibility::Visible
+                        ))
+                        .insert(MiloObject {
+                            id: obj_id,
+                            name: text.name.to_owned(),
+                            dir: obj_dir_name.to_owned(),
+                        })
+                        .insert(MiloTransProps::from_trans(text))
+                        .insert(MiloText {
+                            text: text.text.to_owned(),
+                            font: text.font.to_owned(),
+                        })
+                        .insert(MiloPickable)
+                        .id();
+
+                    if let Some(callback) = callback {
+                        let mut entity_command = commands.entity(text_entity);
+                        callback(&mut entity_command);
+                    }
+
+                    commands
+                        .entity(root_entity)
+                        .add_child(text_entity);
+
+                    log::info!("Loaded text: {}", text.get_name());
+                },
+                Object::Tex(tex) => {
+                    // Not spatial, only holds props
+                    let tex_entity = commands
+                        .spawn(Name::new(tex.name.to_owned()))
+                        .insert(MiloObject {
+                            id: obj_id,
+                            name: tex.name.to_owned(),
+                            dir: obj_dir_name.to_owned(),
+                        })
+                        .insert(MiloTexProps::from(tex))
+                        .id();
+
+                    if let Some(callback) = callback {
+                        let mut entity_command = commands.entity(tex_entity);
+                        callback(&mut entity_command);
+                    }
+
+                    log::info!("Loaded tex: {}", tex.get_name());
+                },
                 Object::Trans(trans) => {
                     let mat = map_matrix(trans.get_local_xfm());
 
@@ -506,10 +628,11 @@ fn process_milo_scene_events(
                         .spawn(Name::new(trans.name.to_owned()))
                         .insert((Transform::from_matrix(mat), Visibility::Visible))
                         .insert(MiloObject {
-                            id: (start_idx + i) as u32,
+                            id: obj_id,
                             name: trans.name.to_owned(),
                             dir: obj_dir_name.to_owned(),
                         })
+                        .insert(MiloTransProps::from_trans(trans))
                         .id();
 
                     if let Some(callback) = callback {
@@ -527,18 +650,22 @@ fn process_milo_scene_events(
             }
         }
 
-        // Add image tasks to components
-        for (name, (task, mats)) in textures_to_load.into_iter() {
+        // Decode textures for new materials (cached materials already have them)
+        for (name, mats) in tex_bindings.into_iter().filter(|(t, _)| textures_in_use.contains(t)) {
+            let Some(tex) = milo_textures.get(name).filter(|t| t.bitmap.is_some()) else {
+                continue;
+            };
+
             commands
                 .spawn(MiloAsyncTexture {
+                    scene: scene_id,
                     tex_name: name.to_owned(),
-                    image_task: task,
+                    image_task: spawn_texture_task(thread_pool, tex, sys_info),
                     mat_handles: mats
                 });
         }
 
         milos_updated = true;
-        state.objects.append(milo.get_entries_mut());
         scene_events_writer.write(LoadMiloSceneComplete(milo_path.to_owned()));
     }
 
@@ -552,6 +679,7 @@ fn update_milo_object_parents(
     state: Res<MiloState>,
     root_query: Query<Entity, With<MiloRoot>>,
     milo_objects_query: Query<(Entity, &MiloObject), With<Transform>>,
+    attach_query: Query<&MiloAttachTo>,
     update_parents_events_reader: EventReader<UpdateMiloObjectParents>,
 ) {
     if update_parents_events_reader.is_empty() {
@@ -564,20 +692,22 @@ fn update_milo_object_parents(
 
     let obj_entities = milo_objects_query
         .iter()
-        .map(|(en, mo)| (en, &state.objects[mo.id as usize]))
+        .flat_map(|(en, mo)| state.get_object(mo.id).map(|o| (en, o)))
         .collect::<Vec<_>>();
 
     /*let (entity_map, children_map) = obj_entities
         .iter()
-        .map(|(en, mo)| (en, &state.objects[mo.id as usize]))
+        .map(|(en, mo)| (en, state.get_object(mo.id).unwrap()))
         .fold((HashMap::new(), HashMap::new()), |(mut entity_acc, mut children_acc), (en, obj)| {
             entity_acc.insert(obj.get_name(), en.clone());
 
             let trans_parent = match obj {
                 Object::BandPlacer(obj) => &obj.parent,
                 Object::Cam(obj) => &obj.parent,
+                Object::CharCollide(obj) => &obj.parent,
                 Object::Mesh(obj) => &obj.parent,
                 Object::Group(obj) => &obj.parent,
+                Object::Text(obj) => &obj.parent,
                 Object::Trans(obj) => &obj.parent,
                 _ => {
                     return (entity_acc, children_acc);
@@ -596,7 +726,7 @@ fn update_milo_object_parents(
             (entity_acc, children_acc)
         });*/
 
-    log::debug!("Found {}/{} objects!", obj_entities.len(), state.objects.len());
+    log::debug!("Found {}/{} objects!", obj_entities.len(), state.get_object_count());
 
     let (entity_map, parent_map) = obj_entities
         .iter()
@@ -606,8 +736,10 @@ fn update_milo_object_parents(
             let trans_parent = match obj {
                 Object::BandPlacer(obj) => &obj.parent,
                 Object::Cam(obj) => &obj.parent,
+                Object::CharCollide(obj) => &obj.parent,
                 Object::Mesh(obj) => &obj.parent,
                 Object::Group(obj) => &obj.parent,
+                Object::Text(obj) => &obj.parent,
                 Object::Trans(obj) => &obj.parent,
                 _ => {
                     return (entity_acc, parent_acc);
@@ -641,6 +773,7 @@ fn update_milo_object_parents(
 
                 entity
             })
+            .or_else(|| attach_query.get(entity).ok().map(|MiloAttachTo(en)| *en))
             .unwrap_or(root_entity);
 
         commands
@@ -654,12 +787,24 @@ fn process_milo_async_textures(
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut images: ResMut<Assets<Image>>,
     mut task_query: Query<(Entity, &mut MiloAsyncTexture)>,
+    tex_query: Query<(Entity, &MiloObject), With<MiloTexProps>>,
 ) {
     for (entity, mut async_tex) in &mut task_query {
         if let Some(img) = future::block_on(future::poll_once(&mut async_tex.image_task)) {
             // Add texture
             let img_handle = images.add(img);
 
+            // Keep on tex so mat props can switch textures
+            let tex_entity = tex_query
+                .iter()
+                .find(|(_, obj)| obj.id.scene == async_tex.scene && obj.name.eq(&async_tex.tex_name));
+
+            if let Some((tex_entity, _)) = tex_entity {
+                commands
+                    .entity(tex_entity)
+                    .insert(MiloTexImage(img_handle.clone()));
+            }
+
             // Update material
             for (mat_handle, tex_type) in async_tex.mat_handles.iter() {
                 let mat = materials.get_mut(mat_handle).unwrap(); // Shouldn't fail
@@ -683,6 +828,285 @@ fn process_milo_async_textures(
     }
 }
 
+fn map_mesh(mesh: &MeshObject) -> Mesh {
+    let mut bevy_mesh = Mesh::new(bevy::render::render_resource::PrimitiveTopology::TriangleList, bevy::render::render_asset::RenderAssetUsages::RENDER_WORLD | bevy::render::render_asset::RenderAssetUsages::MAIN_WORLD);
+
+    let vert_count = mesh.get_vertices().len();
+
+    let mut positions = vec![Default::default(); vert_count];
+    let mut normals = vec![Default::default(); vert_count];
+    let mut tangents = vec![Default::default(); vert_count];
+    let mut uvs = vec![Default::default(); vert_count];
+
+    for (i, vert) in mesh.get_vertices().iter().enumerate() {
+        positions[i] = [vert.pos.x, vert.pos.y, vert.pos.z];
+
+        // TODO: Figure out normals/tangents
+        //normals.push([vert.normals.x, vert.normals.y, vert.normals.z]);
+        normals[i] = [1.0, 1.0, 1.0];
+        tangents[i] = [0.0, 0.0, 0.0, 1.0];
+
+        uvs[i] = [vert.uv.u, vert.uv.v];
+    }
+
+    let indices = bevy::render::mesh::Indices::U16(
+        mesh.faces.iter().flat_map(|f| *f).collect()
+    );
+
+    bevy_mesh.insert_indices(indices);
+    bevy_mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    bevy_mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    bevy_mesh.insert_attribute(Mesh::ATTRIBUTE_TANGENT, tangents);
+    bevy_mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+
+    bevy_mesh
+}
+
+fn map_material(milo_mat: Option<&MatObject>) -> StandardMaterial {
+    match milo_mat {
+        Some(mat) => StandardMaterial {
+            //alpha_mode: AlphaMode::Blend,
+            alpha_mode: match (mat.blend, mat.z_mode) {
+                (Blend::kBlendSrcAlpha, ZMode::kZModeDisable) => AlphaMode::Blend,
+                _ => AlphaMode::Opaque
+            },
+            base_color: Color::srgba(
+                mat.color.r,
+                mat.color.g,
+                mat.color.b,
+                mat.alpha,
+            ),
+            double_sided: true,
+            unlit: true,
+            base_color_texture: None,
+            normal_map_texture: None,
+            emissive_texture: None,
+            //roughness: 0.8, // TODO: Bevy 0.6 migration
+            /*base_color_texture: get_texture(&mut loader, &mat.diffuse_tex, system_info)
+                .and_then(map_texture)
+                .and_then(|t| Some(bevy_textures.add(t))),
+            normal_map: get_texture(&mut loader, &mat.norm_detail_map, system_info)
+                .and_then(map_texture)
+                .and_then(|t| Some(bevy_textures.add(t))),
+            emissive_texture: get_texture(&mut loader, &mat.emissive_map, system_info)
+                .and_then(map_texture)
+                .and_then(|t| Some(bevy_textures.add(t))),*/
+            ..Default::default()
+        },
+        None => StandardMaterial {
+            base_color: Color::srgb(0.3, 0.5, 0.3),
+            double_sided: true,
+            unlit: false,
+            ..Default::default()
+        },
+    }
+}
+
+/// Re-uses material if already loaded, otherwise creates it and queues textures
+fn get_or_add_material<'a>(
+    asset_cache: &mut MiloAssetCache,
+    materials: &mut Assets<StandardMaterial>,
+    tex_bindings: &mut HashMap<&'a String, Vec<(Handle<StandardMaterial>, TextureType)>>,
+    scene_id: MiloSceneId,
+    milo_mat: Option<&'a MatObject>,
+) -> Handle<StandardMaterial> {
+    let Some(mat) = milo_mat else {
+        return asset_cache.default_material
+            .get_or_insert_with(|| materials.add(map_material(None)))
+            .clone();
+    };
+
+    let mat_key = get_mat_key(scene_id, mat);
+
+    if let Some(handle) = asset_cache.materials.get(&mat_key) {
+        return handle.clone();
+    }
+
+    let handle = materials.add(map_material(Some(mat)));
+    asset_cache.materials.insert(mat_key, handle.clone());
+
+    let textures = [
+        (&mat.diffuse_tex, TextureType::Diffuse),
+        (&mat.normal_map, TextureType::Normal),
+        (&mat.emissive_map, TextureType::Emissive),
+    ];
+
+    for (tex_name, typ) in textures.into_iter().filter(|(t, _)| !t.is_empty()) {
+        tex_bindings
+            .entry(tex_name)
+            .or_default()
+            .push((handle.clone(), typ));
+    }
+
+    handle
+}
+
+fn get_mesh_key(mesh: &MeshObject) -> MiloMeshKey {
+    MiloMeshKey {
+        verts: mesh.get_vertices()
+            .iter()
+            .map(|v| [v.pos.x, v.pos.y, v.pos.z, v.uv.u, v.uv.v].map(f32::to_bits))
+            .collect(),
+        faces: mesh.faces.to_owned(),
+    }
+}
+
+/// Identical mats in scene share material, edited mats get their own copy
+fn get_mat_key(scene_id: MiloSceneId, mat: &MatObject) -> MiloMatKey {
+    MiloMatKey {
+        scene: scene_id,
+        blend: mat.blend as u32,
+        z_mode: mat.z_mode as u32,
+        color: [mat.color.r, mat.color.g, mat.color.b, mat.alpha].map(f32::to_bits),
+        diffuse_tex: mat.diffuse_tex.to_owned(),
+        normal_map: mat.normal_map.to_owned(),
+        emissive_map: mat.emissive_map.to_owned(),
+    }
+}
+
+fn spawn_texture_task(thread_pool: &AsyncComputeTaskPool, tex: &Tex, sys_info: &SystemInfo) -> Task<Image> {
+    let sys_info = sys_info.clone();
+
+    let bitmap = tex.bitmap
+        .as_ref()
+        .unwrap()
+        .clone();
+
+    thread_pool.spawn(async move {
+        // Decode texture
+        let (decoded, bpp, format) = match (&sys_info.platform, bitmap.encoding) {
+            (Platform::X360 | Platform::PS3, enc @ (8 | 24 | 32)) => {
+                let mut data = bitmap.raw_data;
+
+                if sys_info.platform.eq(&Platform::X360) {
+                    // Swap bytes
+                    for ab in data.chunks_mut(2) {
+                        let tmp = ab[0];
+
+                        ab[0] = ab[1];
+                        ab[1] = tmp;
+                    }
+                }
+
+                let format = match enc {
+                    24 => TextureFormat::Bc3RgbaUnormSrgb, // DXT5
+                    32 => TextureFormat::Bc5RgUnorm,       // ATI2
+                    _  => TextureFormat::Bc1RgbaUnormSrgb, // DXT1
+                };
+
+                (data, bitmap.bpp as usize, format)
+            },
+            _ => {
+                let data = bitmap.unpack_rgba(&sys_info)
+                    .expect("Can't decode \"{name}\" texture");
+
+                (data, 32, TextureFormat::Rgba8UnormSrgb)
+            }
+        };
+
+        let Bitmap { width, height, mip_maps, .. } = bitmap;
+
+        let tex_size = ((width as usize) * (height as usize) * bpp) / 8;
+        let use_mips = false; // TODO: Always support mips?
+
+        let img_slice = if use_mips {
+            &decoded
+        } else {
+            &decoded[..tex_size]
+        };
+
+        let image_new_fn = match format {
+            TextureFormat::Rgba8UnormSrgb => image_new_fill, // Use fill method for older textures
+            _ => image_new,
+        };
+
+        let mut texture = /*Image::new_fill*/ image_new_fn(
+            Extent3d {
+                width: width.into(),
+                height: height.into(),
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            img_slice,
+            format
+        );
+
+        // Update texture wrap mode
+        texture.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+            address_mode_u: ImageAddressMode::Repeat,
+            address_mode_v: ImageAddressMode::Repeat,
+            anisotropy_clamp: 1, // 16,
+            ..ImageSamplerDescriptor::default()
+        });
+
+        // Set mipmap level
+        if use_mips {
+            texture.texture_descriptor.mip_level_count = mip_maps as u32 + 1;
+        }
+
+        texture
+    })
+}
+
+fn process_set_active_milo_cam(
+    mut set_cam_events_reader: EventReader<SetActiveMiloCam>,
+    mut cam_query: Query<(&mut Camera, &Name)>,
+) {
+    // Only last event matters
+    let Some(SetActiveMiloCam(cam_name)) = set_cam_events_reader.read().last() else {
+        return;
+    };
+
+    if !cam_query.iter().any(|(_, name)| name.as_str() == cam_name) {
+        log::warn!("Can't find cam \"{cam_name}\"");
+        return;
+    }
+
+    for (mut cam, name) in cam_query.iter_mut() {
+        let is_active = name.as_str() == cam_name;
+
+        if cam.is_active != is_active {
+            cam.is_active = is_active;
+        }
+    }
+
+    log::debug!("Cam {cam_name} is now active");
+}
+
+fn update_milo_cam_projections(
+    mut cam_query: Query<(&Camera, &mut Projection), With<MiloCam>>,
+) {
+    for (cam, mut projection) in cam_query.iter_mut() {
+        // Match aspect ratio to render target
+        let Some(size) = cam.logical_viewport_size() else {
+            continue;
+        };
+
+        if size.x <= 0.0 || size.y <= 0.0 {
+            continue;
+        }
+
+        let aspect_ratio = size.x / size.y;
+
+        let Projection::Perspective(persp) = projection.as_ref() else {
+            continue;
+        };
+
+        if (persp.aspect_ratio - aspect_ratio).abs() <= f32::EPSILON {
+            continue;
+        }
+
+        if let Projection::Perspective(persp) = projection.as_mut() {
+            persp.aspect_ratio = aspect_ratio;
+        }
+    }
+}
+
+/// Milo cams look down +Y with +Z up, bevy cams look down -Z with +Y up
+pub fn map_cam_basis() -> Mat4 {
+    Mat4::from_rotation_x(std::f32::consts::FRAC_PI_2)
+}
+
 pub fn map_matrix(m: &Matrix) -> Mat4 {
     Mat4::from_cols_array(&[
         m.m11,
@@ -0,0 +1,227 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use pikaxe::scene::{Blend, GroupObject, MatObject, MeshObject, Tex, Trans, ZMode};
+use std::collections::HashMap;
+
+// Editable mirrors of milo object properties. Changes are synced back to bevy
+// transforms/projections/materials, the source ObjectDir is left untouched.
+// Tex and group props have nothing to sync to so they're read-only.
+
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct MiloTransProps {
+    pub local_xfm: Mat4,
+    pub parent: String,
+}
+
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct MiloMeshProps {
+    pub mat: String,
+}
+
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct MiloMatProps {
+    pub color: Color, // Includes alpha
+    pub alpha_blend: bool,
+    pub diffuse_tex: String,
+    pub normal_map: String,
+    pub emissive_map: String,
+}
+
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct MiloCamProps {
+    pub y_fov: f32,
+    pub near_plane: f32,
+    pub far_plane: f32,
+}
+
+#[derive(Component, Default, Reflect)]
+#[component(immutable)]
+#[reflect(Component)]
+pub struct MiloTexProps {
+    pub width: u32,
+    pub height: u32,
+    pub bpp: u32,
+    pub ext_path: String,
+}
+
+#[derive(Component, Default, Reflect)]
+#[component(immutable)]
+#[reflect(Component)]
+pub struct MiloGroupProps {
+    pub objects: Vec<String>,
+}
+
+impl MiloTransProps {
+    pub fn from_trans<T: Trans + ?Sized>(trans: &T) -> Self {
+        Self {
+            local_xfm: map_matrix(trans.get_local_xfm()),
+            parent: trans.get_parent().to_owned(),
+        }
+    }
+}
+
+impl From<&MeshObject> for MiloMeshProps {
+    fn from(mesh: &MeshObject) -> Self {
+        Self {
+            mat: mesh.mat.to_owned(),
+        }
+    }
+}
+
+impl From<&MatObject> for MiloMatProps {
+    fn from(mat: &MatObject) -> Self {
+        Self {
+            color: Color::srgba(mat.color.r, mat.color.g, mat.color.b, mat.alpha),
+            alpha_blend: matches!((mat.blend, mat.z_mode), (Blend::kBlendSrcAlpha, ZMode::kZModeDisable)),
+            diffuse_tex: mat.diffuse_tex.to_owned(),
+            normal_map: mat.normal_map.to_owned(),
+            emissive_map: mat.emissive_map.to_owned(),
+        }
+    }
+}
+
+impl From<&Tex> for MiloTexProps {
+    fn from(tex: &Tex) -> Self {
+        Self {
+            width: tex.width as u32,
+            height: tex.height as u32,
+            bpp: tex.bpp as u32,
+            ext_path: tex.ext_path.to_owned(),
+        }
+    }
+}
+
+impl From<&GroupObject> for MiloGroupProps {
+    fn from(group: &GroupObject) -> Self {
+        Self {
+            objects: group.objects.to_owned(),
+        }
+    }
+}
+
+pub(crate) fn sync_milo_trans_props(
+    mut props_query: Query<(&MiloTransProps, &mut Transform, Option<&MiloCam>), Changed<MiloTransProps>>,
+) {
+    for (props, mut trans, cam) in props_query.iter_mut() {
+        let mat = match cam {
+            Some(_) => props.local_xfm * map_cam_basis(),
+            _ => props.local_xfm
+        };
+
+        *trans = Transform::from_matrix(mat);
+    }
+}
+
+pub(crate) fn sync_milo_cam_props(
+    mut props_query: Query<(&MiloCamProps, &mut Projection), Changed<MiloCamProps>>,
+) {
+    for (props, mut projection) in props_query.iter_mut() {
+        // Clip planes are in milo units, cam inherits scale from root
+        if let Projection::Perspective(persp) = projection.as_mut() {
+            persp.fov = props.y_fov;
+            persp.near = props.near_plane;
+            persp.far = props.far_plane;
+        }
+    }
+}
+
+pub(crate) fn sync_milo_mat_props(
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut mat_query: Query<(&MiloObject, Ref<MiloMatProps>, &mut MiloMaterial)>,
+    mut mesh_query: Query<(&MiloObject, &MiloMeshProps, &mut MeshMaterial3d<StandardMaterial>)>,
+    tex_query: Query<(&MiloObject, &MiloTexImage)>,
+) {
+    // Props are also changed when added
+    let is_edited = |props: &Ref<MiloMatProps>| props.is_changed() && !props.is_added();
+
+    if !mat_query.iter().any(|(_, props, _)| is_edited(&props)) {
+        return;
+    }
+
+    // Identical mats share material until edited
+    let mut handle_counts = HashMap::new();
+
+    for (_, _, MiloMaterial(handle)) in mat_query.iter() {
+        *handle_counts.entry(handle.id()).or_insert(0usize) += 1;
+    }
+
+    for (obj, props, mut milo_mat) in mat_query.iter_mut() {
+        if !is_edited(&props) {
+            continue;
+        }
+
+        if handle_counts.get(&milo_mat.0.id()).is_some_and(|c| *c > 1) {
+            let Some(mat_copy) = materials.get(&milo_mat.0).cloned() else {
+                continue;
+            };
+
+            let old_handle = std::mem::replace(&mut milo_mat.0, materials.add(mat_copy));
+
+            if let Some(count) = handle_counts.get_mut(&old_handle.id()) {
+                *count -= 1;
+            }
+
+            // Move meshes using this mat to copy
+            for (mesh_obj, mesh_props, mut mesh_mat) in mesh_query.iter_mut() {
+                if mesh_mat.0 == old_handle && mesh_obj.id.scene == obj.id.scene && mesh_props.mat.eq(&obj.name) {
+                    mesh_mat.0 = milo_mat.0.clone();
+                }
+            }
+        }
+
+        let Some(mat) = materials.get_mut(&milo_mat.0) else {
+            continue;
+        };
+
+        mat.base_color = props.color;
+        mat.alpha_mode = match props.alpha_blend {
+            true => AlphaMode::Blend,
+            _ => AlphaMode::Opaque
+        };
+
+        mat.base_color_texture = get_tex_image(obj, &props.diffuse_tex, &tex_query);
+        mat.normal_map_texture = get_tex_image(obj, &props.normal_map, &tex_query);
+        mat.emissive_texture = get_tex_image(obj, &props.emissive_map, &tex_query);
+    }
+}
+
+fn get_tex_image(mat_obj: &MiloObject, tex_name: &str, tex_query: &Query<(&MiloObject, &MiloTexImage)>) -> Option<Handle<Image>> {
+    if tex_name.is_empty() {
+        return None;
+    }
+
+    // Tex names are only unique within scene
+    let image = tex_query
+        .iter()
+        .find(|(tex_obj, _)| tex_obj.id.scene == mat_obj.id.scene && tex_obj.name.eq(tex_name))
+        .map(|(_, MiloTexImage(h))| h.clone());
+
+    if image.is_none() {
+        log::warn!("Can't find loaded tex \"{}\" for mat \"{}\"", tex_name, &mat_obj.name);
+    }
+
+    image
+}
+
+pub(crate) fn sync_milo_mesh_props(
+    mut props_query: Query<(&MiloObject, &MiloMeshProps, &mut MeshMaterial3d<StandardMaterial>), Changed<MiloMeshProps>>,
+    mat_query: Query<(&MiloObject, &MiloMaterial)>,
+) {
+    for (obj, props, mut mesh_mat) in props_query.iter_mut() {
+        // Mat names are only unique within scene
+        let mat_handle = mat_query
+            .iter()
+            .find(|(mat_obj, _)| mat_obj.id.scene == obj.id.scene && mat_obj.name.eq(&props.mat))
+            .map(|(_, MiloMaterial(h))| h);
+
+        match mat_handle {
+            Some(h) if mesh_mat.0.ne(h) => mesh_mat.0 = h.clone(),
+            Some(_) => {},
+            None => log::warn!("Can't find mat \"{}\" for mesh \"{}\"", &props.mat, obj.name)
+        }
+    }
+}
@@ -1,52 +1,661 @@
 use bevy::prelude::*;
 use pikaxe::SystemInfo;
-use pikaxe::ark::{Ark, ArkOffsetEntry};
+use pikaxe::ark::Ark;
 use pikaxe::io::*;
 use pikaxe::scene::Object;
 use pikaxe::scene::ObjectDir;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Shared mesh + material handles so identical geometry isn't uploaded twice
+#[derive(Default, Resource)]
+pub struct MiloAssetCache {
+    pub meshes: HashMap<MiloMeshKey, Handle<Mesh>>,
+    pub materials: HashMap<MiloMatKey, Handle<StandardMaterial>>,
+    pub default_material: Option<Handle<StandardMaterial>>, // Used when mesh mat is missing
+    pub spheres: HashMap<u32, Handle<Mesh>>, // Radius bits -> sphere mesh
+    pub sphere_material: Option<Handle<StandardMaterial>>,
+}
+
+impl MiloAssetCache {
+    /// Drops all handles so unused assets can be freed
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Geometry mesh was built from (floats compared by bits)
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct MiloMeshKey {
+    pub verts: Vec<[u32; 5]>, // Pos + uv
+    pub faces: Vec<[u16; 3]>,
+}
+
+/// Mat properties material was built from (floats compared by bits)
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct MiloMatKey {
+    pub scene: MiloSceneId, // Textures are only unique within scene
+    pub blend: u32,
+    pub z_mode: u32,
+    pub color: [u32; 4], // Includes alpha
+    pub diffuse_tex: String,
+    pub normal_map: String,
+    pub emissive_map: String,
+}
+
+/// Up axis of bevy world (milo scenes are Z-up)
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum MiloAxisConvention {
+    #[default]
+    YUp, // Bevy convention
+    ZUp, // Keep milo axes
+}
+
+pub const DEFAULT_UNIT_SCALE: f32 = 0.1;
+
+/// Conversion from milo space to bevy world space
+#[derive(Clone, Copy, Debug, Resource)]
+pub struct MiloWorldSettings {
+    pub axis: MiloAxisConvention,
+    pub unit_scale: f32, // World units per milo unit
+}
+
+impl Default for MiloWorldSettings {
+    fn default() -> Self {
+        Self {
+            axis: MiloAxisConvention::default(),
+            unit_scale: DEFAULT_UNIT_SCALE,
+        }
+    }
+}
+
+impl MiloWorldSettings {
+    pub fn get_world_matrix(&self) -> Mat4 {
+        let axis_mat = match self.axis {
+            MiloAxisConvention::YUp => Mat4::from_cols_array(&[
+                -1.0,  0.0,  0.0, 0.0,
+                0.0,  0.0,  1.0, 0.0,
+                0.0,  1.0,  0.0, 0.0,
+                0.0,  0.0,  0.0, 1.0,
+            ]),
+            MiloAxisConvention::ZUp => Mat4::IDENTITY,
+        };
+
+        axis_mat * Mat4::from_scale(Vec3::splat(self.unit_scale))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum MiloSourcePath {
+    Ark(PathBuf),
+    Directory(PathBuf),
+}
+
+/// Creates unpacked milo on demand (i.e. test fixtures)
+pub type MiloDirFactory = Box<dyn Fn() -> (SystemInfo, ObjectDir) + Send + Sync>;
+
+pub enum MiloSource {
+    Ark(Ark, MiloPathIndex),
+    Directory(PathBuf, MiloPathIndex), // Extracted game files
+    Memory(Vec<MiloDirFactory>, MiloPathIndex),
+}
+
+impl MiloSource {
+    pub fn from_ark(ark: Ark) -> MiloSource {
+        let index = MiloPathIndex::new(ark
+            .entries
+            .iter()
+            .map(|e| e.path.to_owned())
+            .collect());
+
+        MiloSource::Ark(ark, index)
+    }
+
+    pub fn from_dir<T: AsRef<Path>>(dir: T) -> MiloSource {
+        let dir = dir.as_ref();
+        let mut files = Vec::new();
+
+        find_files_recursive(dir, dir, &mut files);
+
+        MiloSource::Directory(dir.to_owned(), MiloPathIndex::new(files))
+    }
+
+    pub fn from_memory<I: IntoIterator<Item = (String, MiloDirFactory)>>(milos: I) -> MiloSource {
+        let (paths, factories) = milos
+            .into_iter()
+            .unzip();
+
+        MiloSource::Memory(factories, MiloPathIndex::new(paths))
+    }
+
+    pub fn from_source_path(source_path: &MiloSourcePath) -> Option<MiloSource> {
+        match source_path {
+            MiloSourcePath::Ark(path) => match Ark::from_path(path) {
+                Ok(ark) => Some(MiloSource::from_ark(ark)),
+                Err(err) => {
+                    log::error!("Can't open ark file \"{}\": {:?}", path.display(), err);
+                    None
+                }
+            },
+            MiloSourcePath::Directory(path) if path.is_dir() => Some(MiloSource::from_dir(path)),
+            MiloSourcePath::Directory(path) => {
+                log::error!("Can't find directory \"{}\"", path.display());
+                None
+            }
+        }
+    }
+
+    fn get_index(&self) -> &MiloPathIndex {
+        match self {
+            MiloSource::Ark(_, index)
+                | MiloSource::Directory(_, index)
+                | MiloSource::Memory(_, index) => index,
+        }
+    }
+
+    pub fn get_paths(&self) -> &[String] {
+        self.get_index().get_paths()
+    }
+
+    pub fn resolve_path(&self, path: &str) -> Result<&str, MiloPathError> {
+        let index = self.get_index();
+
+        index
+            .resolve(path)
+            .map(|i| index.get_path(i))
+    }
+
+    /// Returns resolved path + data
+    pub fn read_file(&self, path: &str) -> Result<(String, Vec<u8>), MiloPathError> {
+        match self {
+            MiloSource::Ark(ark, index) => {
+                let entry = &ark.entries[index.resolve(path)?];
+
+                let data = ark
+                    .get_stream(entry.id)
+                    .map_err(|_| MiloPathError::Read(entry.path.to_owned()))?;
+
+                Ok((entry.path.to_owned(), data))
+            },
+            MiloSource::Directory(dir, index) => {
+                let rel_path = index.get_path(index.resolve(path)?);
+
+                let data = std::fs::read(dir.join(rel_path))
+                    .map_err(|_| MiloPathError::Read(rel_path.to_owned()))?;
+
+                Ok((rel_path.to_owned(), data))
+            },
+            MiloSource::Memory(..) => {
+                // Only unpacked milos, let lower sources read file
+                Err(MiloPathError::NotFound(path.to_owned()))
+            }
+        }
+    }
+
+    /// Returns resolved path + unpacked milo
+    pub fn open_milo(&self, path: &str) -> Result<(String, SystemInfo, ObjectDir), MiloPathError> {
+        if let MiloSource::Memory(factories, index) = self {
+            let i = index.resolve(path)?;
+            let (system_info, obj_dir) = factories[i]();
+
+            return Ok((index.get_path(i).to_owned(), system_info, obj_dir));
+        }
+
+        let (entry_path, data) = self.read_file(path)?;
+
+        let mut stream = MemoryStream::from_slice_as_read(&data);
+        let milo = MiloArchive::from_stream(&mut stream)
+            .map_err(|_| MiloPathError::Unpack(entry_path.to_owned()))?;
+
+        let system_info = SystemInfo::guess_system_info(&milo, Path::new(&entry_path));
+
+        let mut obj_dir = milo.unpack_directory(&system_info)
+            .map_err(|_| MiloPathError::Unpack(entry_path.to_owned()))?;
+
+        obj_dir.unpack_entries(&system_info)
+            .map_err(|_| MiloPathError::Unpack(entry_path.to_owned()))?;
+
+        Ok((entry_path, system_info, obj_dir))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct MiloSceneId {
+    index: u32,
+    generation: u32,
+}
+
+/// Stable handle to object in loaded scene, becomes invalid when object or scene is removed
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct MiloObjectId {
+    pub scene: MiloSceneId,
+    slot: u32,
+    generation: u32,
+}
+
+#[derive(Default)]
+struct MiloObjectSlot {
+    generation: u32,
+    entry: Option<u32>, // Index into dir entries
+}
+
+pub struct MiloScene {
+    pub path: String,
+    pub system_info: SystemInfo,
+    dir: ObjectDir,
+    slots: Vec<MiloObjectSlot>,
+    entry_slots: Vec<u32>, // Dir entry -> slot
+    free_slots: Vec<u32>,
+}
+
+impl MiloScene {
+    pub fn new(path: String, system_info: SystemInfo, dir: ObjectDir) -> MiloScene {
+        let entry_count = dir.get_entries().len() as u32;
+
+        MiloScene {
+            path,
+            system_info,
+            dir,
+            slots: (0..entry_count)
+                .map(|i| MiloObjectSlot { generation: 0, entry: Some(i) })
+                .collect(),
+            entry_slots: (0..entry_count).collect(),
+            free_slots: Vec::new(),
+        }
+    }
+
+    /// Entries can only be changed through scene so object ids stay valid
+    pub fn get_dir(&self) -> &ObjectDir {
+        &self.dir
+    }
+
+    fn get_entry_index(&self, id: MiloObjectId) -> Option<usize> {
+        self.slots
+            .get(id.slot as usize)
+            .filter(|s| s.generation == id.generation)
+            .and_then(|s| s.entry)
+            .map(|e| e as usize)
+    }
+
+    fn get_entries_mut(&mut self) -> &mut Vec<Object> {
+        match &mut self.dir {
+            ObjectDir::ObjectDir(dir) => &mut dir.entries,
+        }
+    }
+
+    fn insert_object(&mut self, scene_id: MiloSceneId, obj: Object) -> MiloObjectId {
+        let slot = match self.free_slots.pop() {
+            Some(i) => i,
+            None => {
+                self.slots.push(MiloObjectSlot::default());
+                (self.slots.len() - 1) as u32
+            }
+        };
+
+        let entry = self.dir.get_entries().len() as u32;
+        self.get_entries_mut().push(obj);
+        self.entry_slots.push(slot);
+
+        let obj_slot = &mut self.slots[slot as usize];
+        obj_slot.entry = Some(entry);
+
+        MiloObjectId {
+            scene: scene_id,
+            slot,
+            generation: obj_slot.generation,
+        }
+    }
+
+    fn remove_object(&mut self, id: MiloObjectId) -> Option<Object> {
+        let entry = self.get_entry_index(id)?;
+
+        let obj = self.get_entries_mut().remove(entry);
+        self.entry_slots.remove(entry);
+
+        // Shift entries after removed one
+        for slot in self.entry_slots[entry..].iter() {
+            if let Some(e) = self.slots[*slot as usize].entry.as_mut() {
+                *e -= 1;
+            }
+        }
+
+        // Invalidate existing id
+        let obj_slot = &mut self.slots[id.slot as usize];
+        obj_slot.entry = None;
+        obj_slot.generation += 1;
+        self.free_slots.push(id.slot);
+
+        Some(obj)
+    }
+}
+
+#[derive(Default)]
+struct MiloSceneSlot {
+    generation: u32,
+    scene: Option<MiloScene>,
+}
 
 #[derive(Default, Resource)]
 pub struct MiloState {
-    pub ark: Option<Ark>,
-    pub objects: Vec<Object>,
+    pub sources: Vec<MiloSource>, // Ordered by priority (last overrides first)
+    scenes: Vec<MiloSceneSlot>,
+    free_slots: Vec<u32>,
 }
 
 impl MiloState {
+    pub fn add_source(&mut self, source: MiloSource) {
+        self.sources.push(source);
+    }
+
+    pub fn insert_scene(&mut self, scene: MiloScene) -> MiloSceneId {
+        let index = match self.free_slots.pop() {
+            Some(i) => i,
+            None => {
+                self.scenes.push(MiloSceneSlot::default());
+                (self.scenes.len() - 1) as u32
+            }
+        };
+
+        let slot = &mut self.scenes[index as usize];
+        slot.scene = Some(scene);
+
+        MiloSceneId {
+            index,
+            generation: slot.generation,
+        }
+    }
+
+    pub fn remove_scene(&mut self, id: MiloSceneId) -> Option<MiloScene> {
+        let slot = self.scenes
+            .get_mut(id.index as usize)
+            .filter(|s| s.generation == id.generation)?;
+
+        let scene = slot.scene.take()?;
+
+        // Invalidate existing ids
+        slot.generation += 1;
+        self.free_slots.push(id.index);
+
+        Some(scene)
+    }
+
+    pub fn clear_scenes(&mut self) {
+        let ids = self.get_scenes()
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+
+        for id in ids {
+            self.remove_scene(id);
+        }
+    }
+
+    pub fn get_scene(&self, id: MiloSceneId) -> Option<&MiloScene> {
+        self.scenes
+            .get(id.index as usize)
+            .filter(|s| s.generation == id.generation)
+            .and_then(|s| s.scene.as_ref())
+    }
+
+    pub fn get_scenes(&self) -> impl Iterator<Item = (MiloSceneId, &MiloScene)> {
+        self.scenes
+            .iter()
+            .enumerate()
+            .flat_map(|(i, s)| s.scene
+                .as_ref()
+                .map(|scene| (MiloSceneId { index: i as u32, generation: s.generation }, scene)))
+    }
+
+    fn get_scene_mut(&mut self, id: MiloSceneId) -> Option<&mut MiloScene> {
+        self.scenes
+            .get_mut(id.index as usize)
+            .filter(|s| s.generation == id.generation)
+            .and_then(|s| s.scene.as_mut())
+    }
+
+    pub fn get_object(&self, id: MiloObjectId) -> Option<&Object> {
+        let scene = self.get_scene(id.scene)?;
+        let entry = scene.get_entry_index(id)?;
+
+        scene.dir
+            .get_entries()
+            .get(entry)
+    }
+
+    /// Objects in dir entry order
+    pub fn get_objects(&self, scene_id: MiloSceneId) -> impl Iterator<Item = (MiloObjectId, &Object)> {
+        self.get_scene(scene_id)
+            .into_iter()
+            .flat_map(move |scene| scene.dir
+                .get_entries()
+                .iter()
+                .zip(scene.entry_slots.iter())
+                .map(move |(obj, slot)| (MiloObjectId {
+                    scene: scene_id,
+                    slot: *slot,
+                    generation: scene.slots[*slot as usize].generation,
+                }, obj)))
+    }
+
+    pub fn insert_object(&mut self, scene_id: MiloSceneId, obj: Object) -> Option<MiloObjectId> {
+        self.get_scene_mut(scene_id)
+            .map(|s| s.insert_object(scene_id, obj))
+    }
+
+    /// Removes object from scene dir, other object ids are unaffected
+    pub fn remove_object(&mut self, id: MiloObjectId) -> Option<Object> {
+        self.get_scene_mut(id.scene)
+            .and_then(|s| s.remove_object(id))
+    }
+
+    pub fn get_object_count(&self) -> usize {
+        self.get_scenes()
+            .map(|(_, s)| s.dir.get_entries().len())
+            .sum()
+    }
+
+    /// Resolves path from highest priority source
+    pub fn resolve_path(&self, path: &str) -> Result<&str, MiloPathError> {
+        self.sources
+            .iter()
+            .rev()
+            .map(|s| s.resolve_path(path))
+            .find(|r| !matches!(r, Err(MiloPathError::NotFound(_))))
+            .unwrap_or_else(|| Err(MiloPathError::NotFound(path.to_owned())))
+    }
+
+    /// Paths from all sources starting with prefix (case-insensitive)
+    pub fn find_paths(&self, prefix: &str) -> Vec<&str> {
+        let prefix = normalize_path(prefix).to_ascii_lowercase();
+        let mut found = HashMap::new();
+
+        for path in self.sources.iter().flat_map(|s| s.get_paths()) {
+            let lower_path = normalize_path(path).to_ascii_lowercase();
+
+            if lower_path.starts_with(&prefix) {
+                found.entry(lower_path).or_insert(path.as_str());
+            }
+        }
+
+        let mut paths = found.into_values().collect::<Vec<_>>();
+        paths.sort();
+        paths
+    }
+
+    pub fn read_file(&self, path: &str) -> Result<(String, Vec<u8>), MiloPathError> {
+        self.sources
+            .iter()
+            .rev()
+            .map(|s| s.read_file(path))
+            .find(|r| !matches!(r, Err(MiloPathError::NotFound(_))))
+            .unwrap_or_else(|| Err(MiloPathError::NotFound(path.to_owned())))
+    }
+
     pub fn open_milo(&self, milo_path: &str) -> Option<(SystemInfo, ObjectDir)> {
-        let ark = self.ark.as_ref()?;
+        let res = self.sources
+            .iter()
+            .rev()
+            .map(|s| s.open_milo(milo_path))
+            .find(|r| !matches!(r, Err(MiloPathError::NotFound(_))))
+            .unwrap_or_else(|| Err(MiloPathError::NotFound(milo_path.to_owned())));
 
-        let entry = get_entry_from_path(ark, milo_path)?;
+        match res {
+            Ok((_, system_info, obj_dir)) => Some((system_info, obj_dir)),
+            Err(err) => {
+                log::warn!("{err}");
+                None
+            }
+        }
+    }
+}
 
-        let data = ark.get_stream(entry.id).ok()?;
+/// Platform suffixes appended to file extensions (i.e. ".milo_xbox")
+pub const PLATFORM_SUFFIXES: [&str; 4] = ["_xbox", "_ps3", "_wii", "_ps2"];
 
-        let mut stream = MemoryStream::from_slice_as_read(&data);
-        let milo = MiloArchive::from_stream(&mut stream).ok()?;
+#[derive(Debug, Error)]
+pub enum MiloPathError {
+    #[error("Can't find \"{0}\"")]
+    NotFound(String),
+    #[error("Path \"{path}\" is ambiguous, matches: {candidates:?}")]
+    Ambiguous {
+        path: String,
+        candidates: Vec<String>,
+    },
+    #[error("Can't read \"{0}\"")]
+    Read(String),
+    #[error("Can't unpack \"{0}\"")]
+    Unpack(String),
+}
+
+/// Path lookup for ark entries or loose files
+///
+/// Resolves in order, first stage with any match wins:
+/// 1. Exact path (also tried with "gen" sub folder)
+/// 2. Case-insensitive path
+/// 3. Case-insensitive path + platform suffix (see [`PLATFORM_SUFFIXES`])
+///
+/// Multiple matches in a stage are reported as [`MiloPathError::Ambiguous`]
+#[derive(Default)]
+pub struct MiloPathIndex {
+    paths: Vec<String>,
+    exact: HashMap<String, usize>,
+    lower: HashMap<String, Vec<usize>>,
+}
+
+impl MiloPathIndex {
+    pub fn new(paths: Vec<String>) -> MiloPathIndex {
+        let mut exact = HashMap::new();
+        let mut lower = HashMap::new();
+
+        for (i, path) in paths.iter().enumerate() {
+            let path = normalize_path(path);
 
-        let milo_path = std::path::Path::new(&entry.path);
-        let system_info = SystemInfo::guess_system_info(&milo, &milo_path);
+            exact.insert(path.to_owned(), i);
 
-        let mut obj_dir = milo.unpack_directory(&system_info).ok()?;
-        obj_dir.unpack_entries(&system_info).ok()?;
+            lower
+                .entry(path.to_ascii_lowercase())
+                .or_insert_with(Vec::new)
+                .push(i);
+        }
+
+        MiloPathIndex {
+            paths,
+            exact,
+            lower,
+        }
+    }
 
-        Some((system_info, obj_dir))
+    pub fn get_path(&self, idx: usize) -> &str {
+        &self.paths[idx]
     }
+
+    pub fn get_paths(&self) -> &[String] {
+        &self.paths
+    }
+
+    pub fn resolve(&self, path: &str) -> Result<usize, MiloPathError> {
+        let path = normalize_path(path);
+
+        let possible_paths = [
+            path.to_owned(),
+            get_path_with_gen_folder(path),
+        ];
+
+        // 1. Exact
+        if let Some(i) = possible_paths.iter().find_map(|p| self.exact.get(p)) {
+            return Ok(*i);
+        }
+
+        let lower_paths = possible_paths
+            .iter()
+            .map(|p| p.to_ascii_lowercase())
+            .collect::<Vec<_>>();
+
+        // 2. Case-insensitive
+        let matches = lower_paths
+            .iter()
+            .flat_map(|p| self.lower.get(p))
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+
+        if !matches.is_empty() {
+            return self.get_single_match(path, matches);
+        }
+
+        // 3. Platform suffix
+        let matches = lower_paths
+            .iter()
+            .flat_map(|p| PLATFORM_SUFFIXES
+                .iter()
+                .map(move |s| format!("{p}{s}")))
+            .flat_map(|p| self.lower.get(&p))
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+
+        self.get_single_match(path, matches)
+    }
+
+    fn get_single_match(&self, path: &str, mut matches: Vec<usize>) -> Result<usize, MiloPathError> {
+        matches.sort();
+        matches.dedup();
+
+        match matches.as_slice() {
+            [] => Err(MiloPathError::NotFound(path.to_owned())),
+            [i] => Ok(*i),
+            _ => Err(MiloPathError::Ambiguous {
+                path: path.to_owned(),
+                candidates: matches
+                    .iter()
+                    .map(|i| self.paths[*i].to_owned())
+                    .collect(),
+            }),
+        }
+    }
+}
+
+fn normalize_path(path: &str) -> &str {
+    path.trim_start_matches("./")
 }
 
-fn get_entry_from_path<'a>(ark: &'a Ark, path: &str) -> Option<&'a ArkOffsetEntry> {
-    let possible_paths = [
-        path.to_owned(),
-        get_path_with_gen_folder(path),
-    ];
+fn find_files_recursive(root: &Path, dir: &Path, files: &mut Vec<String>) {
+    let Ok(entries) = dir.read_dir() else {
+        return;
+    };
 
-    /*for p in possible_paths.iter() {
-        log::debug!("{p}");
-    }*/
+    for path in entries.flatten().map(|e| e.path()) {
+        if path.is_dir() {
+            find_files_recursive(root, &path, files);
+            continue;
+        }
 
-    ark
-        .entries
-        .iter()
-        .filter(|e| possible_paths.iter().any(|p| e.path.starts_with(p)))
-        .next()
+        if let Ok(rel_path) = path.strip_prefix(root) {
+            files.push(rel_path
+                .to_string_lossy()
+                .replace('\\', "/"));
+        }
+    }
 }
 
 fn get_path_with_gen_folder(path: &str) -> String {
@@ -58,6 +667,4 @@ fn get_path_with_gen_folder(path: &str) -> String {
 
     let (s1, s2) = path.split_at(i);
     format!("{s1}/gen{s2}")
-}
-
-// TODO: Track object hierarchy somehow (object id node tree?)
\ No newline at end of file
+}
\ No newline at end of file
@@ -0,0 +1,496 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use pikaxe::midi::{MidiEvent, MidiFile, MidiTextType};
+use thiserror::Error;
+
+const DEFAULT_TICKS_PER_BEAT: u32 = 480;
+const DEFAULT_MPQ: u32 = 500_000; // 120 bpm
+
+#[derive(Clone, Copy, Debug)]
+pub struct MiloTempo {
+    pub tick: u64,
+    pub seconds: f64,
+    pub mpq: u32, // Microseconds per quarter note
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MiloTimeSig {
+    pub tick: u64,
+    pub measure: f64, // Measure index at tick
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+/// Tick <-> seconds/beat/measure conversion
+#[derive(Clone, Debug)]
+pub struct MiloTempoMap {
+    pub ticks_per_beat: u32,
+    pub tempos: Vec<MiloTempo>,
+    pub time_sigs: Vec<MiloTimeSig>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MiloSongNote {
+    pub tick: u64,
+    pub length: u64, // Ticks
+    pub pitch: u8,
+    pub velocity: u8,
+    pub channel: u8,
+}
+
+#[derive(Clone, Debug)]
+pub struct MiloSongText {
+    pub tick: u64,
+    pub text: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MiloSongTrack {
+    pub name: String,
+    pub notes: Vec<MiloSongNote>, // Sorted by tick
+    pub texts: Vec<MiloSongText>, // Sorted by tick
+}
+
+#[derive(Resource)]
+pub struct MiloSong {
+    pub path: String,
+    pub tempo_map: MiloTempoMap,
+    pub tracks: Vec<MiloSongTrack>,
+    pub length: u64, // Ticks
+}
+
+/// Current song position, advanced while playing
+#[derive(Resource)]
+pub struct MiloSongTime {
+    pub playing: bool,
+    pub speed: f32,
+    pub seconds: f64,
+    pub tick: u64,
+    pub beat: f64,
+    pub measure: f64,
+    pub bpm: f64,
+    pub time_sig: (u32, u32),
+    last_tick: Option<u64>, // Events after this tick are fired
+}
+
+impl Default for MiloSongTime {
+    fn default() -> Self {
+        Self {
+            playing: false,
+            speed: 1.0,
+            seconds: 0.0,
+            tick: 0,
+            beat: 0.0,
+            measure: 0.0,
+            bpm: 120.0,
+            time_sig: (4, 4),
+            last_tick: None,
+        }
+    }
+}
+
+impl MiloSongTime {
+    /// Jumps to time without firing skipped events
+    pub fn seek(&mut self, seconds: f64) {
+        self.seconds = seconds.max(0.0);
+        self.last_tick = None;
+    }
+}
+
+impl MiloTempoMap {
+    fn new(ticks_per_beat: u32, mut tempos: Vec<(u64, u32)>, mut time_sigs: Vec<(u64, u32, u32)>) -> MiloTempoMap {
+        tempos.sort_by_key(|(tick, _)| *tick);
+        time_sigs.sort_by_key(|(tick, ..)| *tick);
+
+        if tempos.first().is_none_or(|(tick, _)| *tick > 0) {
+            tempos.insert(0, (0, DEFAULT_MPQ));
+        }
+
+        if time_sigs.first().is_none_or(|(tick, ..)| *tick > 0) {
+            time_sigs.insert(0, (0, 4, 4));
+        }
+
+        // Pre-compute realtime position for each tempo change
+        let mut seconds = 0.0;
+        let mut prev: Option<(u64, u32)> = None;
+
+        let tempos = tempos
+            .into_iter()
+            .map(|(tick, mpq)| {
+                if let Some((prev_tick, prev_mpq)) = prev {
+                    seconds += ticks_to_seconds(tick - prev_tick, prev_mpq, ticks_per_beat);
+                }
+
+                prev = Some((tick, mpq));
+                MiloTempo { tick, seconds, mpq }
+            })
+            .collect();
+
+        let mut measure = 0.0;
+        let mut prev: Option<(u64, u32, u32)> = None;
+
+        let time_sigs = time_sigs
+            .into_iter()
+            .map(|(tick, numerator, denominator)| {
+                if let Some((prev_tick, prev_num, prev_denom)) = prev {
+                    measure += (tick - prev_tick) as f64 / get_ticks_per_measure(ticks_per_beat, prev_num, prev_denom);
+                }
+
+                prev = Some((tick, numerator, denominator));
+                MiloTimeSig { tick, measure, numerator, denominator }
+            })
+            .collect();
+
+        MiloTempoMap {
+            ticks_per_beat,
+            tempos,
+            time_sigs,
+        }
+    }
+
+    pub fn get_tempo(&self, tick: u64) -> &MiloTempo {
+        let i = self.tempos.partition_point(|t| t.tick <= tick);
+        &self.tempos[i.saturating_sub(1)]
+    }
+
+    pub fn get_time_sig(&self, tick: u64) -> &MiloTimeSig {
+        let i = self.time_sigs.partition_point(|t| t.tick <= tick);
+        &self.time_sigs[i.saturating_sub(1)]
+    }
+
+    pub fn tick_to_seconds(&self, tick: u64) -> f64 {
+        let tempo = self.get_tempo(tick);
+        tempo.seconds + ticks_to_seconds(tick - tempo.tick, tempo.mpq, self.ticks_per_beat)
+    }
+
+    pub fn seconds_to_tick(&self, seconds: f64) -> u64 {
+        let i = self.tempos.partition_point(|t| t.seconds <= seconds);
+        let tempo = &self.tempos[i.saturating_sub(1)];
+
+        let beats = (seconds - tempo.seconds).max(0.0) * 1_000_000.0 / tempo.mpq as f64;
+        tempo.tick + (beats * self.ticks_per_beat as f64) as u64
+    }
+
+    pub fn tick_to_beat(&self, tick: u64) -> f64 {
+        tick as f64 / self.ticks_per_beat as f64
+    }
+
+    pub fn tick_to_measure(&self, tick: u64) -> f64 {
+        let sig = self.get_time_sig(tick);
+        sig.measure + (tick - sig.tick) as f64 / get_ticks_per_measure(self.ticks_per_beat, sig.numerator, sig.denominator)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MiloSongError {
+    #[error("Can't parse midi")]
+    Parse,
+}
+
+impl MiloSong {
+    pub fn from_bytes(path: &str, data: &[u8]) -> Result<MiloSong, MiloSongError> {
+        let mid = MidiFile::from_bytes(data).ok_or(MiloSongError::Parse)?;
+
+        let ticks_per_beat = match mid.ticks_per_quarter {
+            0 => {
+                log::warn!("Unsupported midi timing for \"{path}\", using {DEFAULT_TICKS_PER_BEAT} ticks per beat");
+                DEFAULT_TICKS_PER_BEAT
+            },
+            tpq => tpq as u32,
+        };
+
+        let tempos = mid.tempo
+            .iter()
+            .map(|t| (t.pos, t.mpq))
+            .collect();
+
+        // Time signatures aren't exposed by pikaxe midi, read from raw events
+        let time_sigs = read_time_sigs(data);
+
+        let mut length = mid.tempo
+            .iter()
+            .map(|t| t.pos)
+            .max()
+            .unwrap_or_default();
+
+        let tracks = mid.tracks
+            .iter()
+            .map(|mid_track| {
+                let mut track = MiloSongTrack {
+                    name: mid_track.name.to_owned().unwrap_or_default(),
+                    ..Default::default()
+                };
+
+                for ev in mid_track.events.iter() {
+                    match ev {
+                        MidiEvent::Note(note) => {
+                            track.notes.push(MiloSongNote {
+                                tick: note.pos,
+                                length: note.length,
+                                pitch: note.pitch,
+                                velocity: note.velocity,
+                                channel: note.channel,
+                            });
+
+                            length = length.max(note.pos + note.length);
+                        },
+                        MidiEvent::Meta(meta) => {
+                            let text = match &meta.text {
+                                MidiTextType::Event(text) | MidiTextType::Lyric(text) | MidiTextType::Marker(text) => text,
+                                _ => continue,
+                            };
+
+                            track.texts.push(MiloSongText {
+                                tick: meta.pos,
+                                text: String::from_utf8_lossy(text).into_owned(),
+                            });
+
+                            length = length.max(meta.pos);
+                        },
+                        _ => {}
+                    }
+                }
+
+                track.notes.sort_by_key(|n| n.tick);
+                track.texts.sort_by_key(|t| t.tick);
+
+                track
+            })
+            .collect();
+
+        Ok(MiloSong {
+            path: path.to_owned(),
+            tempo_map: MiloTempoMap::new(ticks_per_beat, tempos, time_sigs),
+            tracks,
+            length,
+        })
+    }
+
+    pub fn get_track(&self, name: &str) -> Option<&MiloSongTrack> {
+        self.tracks
+            .iter()
+            .find(|t| t.name.eq(name))
+    }
+}
+
+pub(crate) fn process_load_milo_song_events(
+    mut commands: Commands,
+    mut song_events_reader: EventReader<LoadMiloSong>,
+    mut song_events_writer: EventWriter<LoadMiloSongComplete>,
+    mut song_time: ResMut<MiloSongTime>,
+    state: Res<MiloState>,
+) {
+    // Only last song matters
+    let Some(LoadMiloSong(song_path)) = song_events_reader.read().last() else {
+        return;
+    };
+
+    let (entry_path, data) = match state.read_file(song_path) {
+        Ok(file) => file,
+        Err(err) => {
+            log::error!("Can't load song: {err}");
+            return;
+        }
+    };
+
+    let song = match MiloSong::from_bytes(&entry_path, &data) {
+        Ok(song) => song,
+        Err(err) => {
+            log::error!("Can't parse song midi \"{entry_path}\": {err}");
+            return;
+        }
+    };
+
+    log::info!("Loaded song: {} ({} tracks)", &entry_path, song.tracks.len());
+
+    *song_time = MiloSongTime::default();
+    commands.insert_resource(song);
+
+    song_events_writer.write(LoadMiloSongComplete(entry_path));
+}
+
+pub(crate) fn update_milo_song_time(
+    time: Res<Time>,
+    song: Option<Res<MiloSong>>,
+    mut song_time: ResMut<MiloSongTime>,
+    mut note_events_writer: EventWriter<MiloSongNoteEvent>,
+    mut text_events_writer: EventWriter<MiloSongTextEvent>,
+) {
+    let Some(song) = song else {
+        return;
+    };
+
+    let tempo_map = &song.tempo_map;
+
+    // Fire events in (last, current] or [start, current] after seek
+    let (start_tick, start_inclusive) = match song_time.last_tick {
+        Some(last) => (last, false),
+        None => (tempo_map.seconds_to_tick(song_time.seconds), true),
+    };
+
+    if song_time.playing {
+        song_time.seconds += time.delta_secs_f64() * song_time.speed as f64;
+    }
+
+    let tick = tempo_map.seconds_to_tick(song_time.seconds);
+    let tempo = tempo_map.get_tempo(tick);
+    let time_sig = tempo_map.get_time_sig(tick);
+
+    song_time.tick = tick;
+    song_time.beat = tempo_map.tick_to_beat(tick);
+    song_time.measure = tempo_map.tick_to_measure(tick);
+    song_time.bpm = 60_000_000.0 / tempo.mpq as f64;
+    song_time.time_sig = (time_sig.numerator, time_sig.denominator);
+
+    if !song_time.playing {
+        return;
+    }
+
+    let is_before_range = |t: u64| match start_inclusive {
+        true => t < start_tick,
+        _ => t <= start_tick,
+    };
+
+    for track in song.tracks.iter() {
+        let first_note = track.notes.partition_point(|n| is_before_range(n.tick));
+
+        for note in track.notes[first_note..].iter().take_while(|n| n.tick <= tick) {
+            let seconds = tempo_map.tick_to_seconds(note.tick);
+
+            note_events_writer.write(MiloSongNoteEvent {
+                track: track.name.to_owned(),
+                note: *note,
+                seconds,
+                length_seconds: tempo_map.tick_to_seconds(note.tick + note.length) - seconds,
+            });
+        }
+
+        let first_text = track.texts.partition_point(|t| is_before_range(t.tick));
+
+        for text in track.texts[first_text..].iter().take_while(|t| t.tick <= tick) {
+            text_events_writer.write(MiloSongTextEvent {
+                track: track.name.to_owned(),
+                text: text.text.to_owned(),
+                seconds: tempo_map.tick_to_seconds(text.tick),
+            });
+        }
+    }
+
+    song_time.last_tick = Some(tick);
+
+    // Stop at end
+    if tick >= song.length {
+        song_time.playing = false;
+        log::debug!("Song \"{}\" finished", &song.path);
+    }
+}
+
+fn ticks_to_seconds(ticks: u64, mpq: u32, ticks_per_beat: u32) -> f64 {
+    (ticks as f64 / ticks_per_beat as f64) * (mpq as f64 / 1_000_000.0)
+}
+
+fn get_ticks_per_measure(ticks_per_beat: u32, numerator: u32, denominator: u32) -> f64 {
+    // Beats are quarter notes
+    (ticks_per_beat as f64 * numerator as f64 * 4.0) / denominator.max(1) as f64
+}
+
+/// Reads time signature meta events from raw midi, returns (tick, numerator, denominator)
+fn read_time_sigs(data: &[u8]) -> Vec<(u64, u32, u32)> {
+    let mut time_sigs = Vec::new();
+
+    if !data.starts_with(b"MThd") || data.len() < 8 {
+        return time_sigs;
+    }
+
+    let mut offset = 8 + read_chunk_size(&data[4..8]);
+
+    while offset + 8 <= data.len() {
+        let start = offset + 8;
+        let end = start + read_chunk_size(&data[(offset + 4)..start]);
+
+        if data[offset..(offset + 4)].eq(b"MTrk") {
+            let track = &data[start..end.min(data.len())];
+
+            if read_track_time_sigs(track, &mut time_sigs).is_none() {
+                log::warn!("Can't read time signatures from midi track");
+            }
+        }
+
+        offset = end;
+    }
+
+    time_sigs
+}
+
+fn read_track_time_sigs(track: &[u8], time_sigs: &mut Vec<(u64, u32, u32)>) -> Option<()> {
+    let mut pos = 0;
+    let mut tick = 0;
+    let mut running_status = 0;
+
+    while pos < track.len() {
+        tick += read_var_len(track, &mut pos)? as u64;
+
+        let status = match *track.get(pos)? {
+            s if s < 0x80 => running_status, // Data byte, re-use last status
+            s => {
+                pos += 1;
+                s
+            }
+        };
+
+        match status {
+            0xFF => {
+                let meta_type = *track.get(pos)?;
+                pos += 1;
+
+                let len = read_var_len(track, &mut pos)? as usize;
+                let meta = track.get(pos..(pos + len))?;
+
+                match meta_type {
+                    0x58 if len >= 2 => time_sigs.push((tick, meta[0] as u32, 1 << (meta[1] & 0x1F))),
+                    0x2F => break, // End of track
+                    _ => {}
+                }
+
+                pos += len;
+            },
+            0xF0 | 0xF7 => {
+                let len = read_var_len(track, &mut pos)? as usize;
+                pos += len;
+            },
+            0x80..=0xEF => {
+                running_status = status;
+
+                pos += match status & 0xF0 {
+                    0xC0 | 0xD0 => 1,
+                    _ => 2,
+                };
+            },
+            _ => return None,
+        }
+    }
+
+    Some(())
+}
+
+fn read_chunk_size(data: &[u8]) -> usize {
+    u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as usize
+}
+
+fn read_var_len(data: &[u8], pos: &mut usize) -> Option<u32> {
+    let mut value = 0u32;
+
+    // Max 4 bytes
+    for _ in 0..4 {
+        let b = *data.get(*pos)?;
+        *pos += 1;
+
+        value = (value << 7) | (b & 0x7F) as u32;
+
+        if b & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+
+    None
+}
@@ -0,0 +1,219 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use pikaxe::scene::ObjectDir;
+use std::collections::HashSet;
+
+/// Band character to place on venue band placer
+#[derive(Clone, Debug)]
+pub struct MiloBandMember {
+    pub placer: String, // i.e. "guitar" or "guitar.bp"
+    pub milo_path: String,
+}
+
+/// Characters loaded with venue
+#[derive(Default, Resource)]
+pub struct MiloVenueBand {
+    pub members: Vec<MiloBandMember>,
+}
+
+#[derive(Default, Resource)]
+pub struct MiloVenueState {
+    pub name: Option<String>,
+    pub milo_paths: Vec<String>, // In load order
+    loaded_paths: HashSet<String>,
+    pending_members: Vec<MiloBandMember>,
+    placed_scenes: HashSet<MiloSceneId>,
+}
+
+/// Finds component milos for venue (i.e. "world/arena/og/arena_geom.milo")
+///
+/// Starts from main venue dir and follows sub dir references, sub dirs are ordered before dirs referencing them
+pub fn find_venue_milos(state: &MiloState, venue: &str) -> Vec<String> {
+    let venue_paths = state
+        .find_paths(&format!("world/{venue}/"))
+        .into_iter()
+        .filter(|p| p.to_ascii_lowercase().contains(".milo"))
+        .map(get_logical_path)
+        .collect::<Vec<_>>();
+
+    let Some(venue_path) = find_venue_path(&venue_paths, venue) else {
+        return Vec::new();
+    };
+
+    let mut visited = HashSet::new();
+    let mut milo_paths = Vec::new();
+
+    add_venue_milo(state, &venue_paths, venue_path, &mut visited, &mut milo_paths);
+
+    milo_paths
+}
+
+fn add_venue_milo(
+    state: &MiloState,
+    venue_paths: &[String],
+    milo_path: &str,
+    visited: &mut HashSet<String>,
+    milo_paths: &mut Vec<String>,
+) {
+    if !visited.insert(milo_path.to_ascii_lowercase()) {
+        return;
+    }
+
+    let Some((_, obj_dir)) = state.open_milo(milo_path) else {
+        return;
+    };
+
+    let sub_dirs = match &obj_dir {
+        ObjectDir::ObjectDir(dir) => &dir.sub_dirs,
+    };
+
+    for sub_dir in sub_dirs.iter() {
+        let sub_dir_name = match sub_dir {
+            ObjectDir::ObjectDir(dir) => &dir.name,
+        };
+
+        match find_venue_path(venue_paths, sub_dir_name) {
+            Some(sub_dir_path) => add_venue_milo(state, venue_paths, sub_dir_path, visited, milo_paths),
+            None => log::debug!("Can't find milo for sub dir \"{sub_dir_name}\" in \"{milo_path}\""),
+        }
+    }
+
+    milo_paths.push(milo_path.to_owned());
+}
+
+fn find_venue_path<'a>(venue_paths: &'a [String], dir_name: &str) -> Option<&'a str> {
+    let dir_name = get_file_stem(dir_name);
+
+    venue_paths
+        .iter()
+        .find(|p| get_file_stem(p).eq(&dir_name))
+        .map(|p| p.as_str())
+}
+
+pub(crate) fn process_load_venue_events(
+    mut venue_events_reader: EventReader<LoadVenue>,
+    mut scene_events_writer: EventWriter<LoadMiloScene>,
+    mut venue_state: ResMut<MiloVenueState>,
+    band: Res<MiloVenueBand>,
+    state: Res<MiloState>,
+) {
+    // Only last venue matters
+    let Some(LoadVenue(venue)) = venue_events_reader.read().last() else {
+        return;
+    };
+
+    let milo_paths = find_venue_milos(&state, venue);
+
+    if milo_paths.is_empty() {
+        log::error!("Can't find milos for venue \"{venue}\"");
+        return;
+    }
+
+    log::info!("Loading venue: {} ({} milos)", venue, milo_paths.len());
+
+    // Sub dirs (i.e. geometry) first, then venue dir, then characters
+    for milo_path in milo_paths.iter() {
+        scene_events_writer.write(LoadMiloScene(milo_path.to_owned()));
+    }
+
+    for member in band.members.iter() {
+        scene_events_writer.write(LoadMiloScene(member.milo_path.to_owned()));
+    }
+
+    *venue_state = MiloVenueState {
+        name: Some(venue.to_owned()),
+        milo_paths,
+        pending_members: band.members.clone(),
+        ..Default::default()
+    };
+}
+
+pub(crate) fn place_venue_band(
+    mut commands: Commands,
+    mut scene_events_reader: EventReader<LoadMiloSceneComplete>,
+    mut venue_events_writer: EventWriter<LoadVenueComplete>,
+    mut update_parents_events_writer: EventWriter<UpdateMiloObjectParents>,
+    mut venue_state: ResMut<MiloVenueState>,
+    state: Res<MiloState>,
+    placer_query: Query<(Entity, &Name), With<MiloBandPlacer>>,
+    objects_query: Query<(Entity, &MiloObject)>,
+) {
+    let Some(venue) = venue_state.name.to_owned() else {
+        scene_events_reader.clear();
+        return;
+    };
+
+    for LoadMiloSceneComplete(milo_path) in scene_events_reader.read() {
+        if venue_state.milo_paths.contains(milo_path) {
+            venue_state.loaded_paths.insert(milo_path.to_owned());
+
+            if venue_state.loaded_paths.len() == venue_state.milo_paths.len() {
+                log::info!("Loaded venue: {venue}");
+                venue_events_writer.write(LoadVenueComplete(venue.to_owned()));
+            }
+        }
+
+        let Some(member_idx) = venue_state.pending_members.iter().position(|m| m.milo_path.eq(milo_path)) else {
+            continue;
+        };
+
+        let member = venue_state.pending_members.remove(member_idx);
+
+        // Newest scene for path (same character can be loaded more than once)
+        let Some(scene_id) = state
+            .get_scenes()
+            .filter(|(id, s)| s.path.eq(milo_path) && !venue_state.placed_scenes.contains(id))
+            .map(|(id, _)| id)
+            .last() else {
+            continue;
+        };
+
+        let placer_name = get_placer_name(&member.placer);
+
+        let Some((placer_entity, _)) = placer_query
+            .iter()
+            .find(|(_, name)| get_placer_name(name.as_str()).eq(&placer_name)) else {
+            log::warn!("Can't find band placer \"{}\" for \"{}\"", &member.placer, milo_path);
+            continue;
+        };
+
+        for (entity, _) in objects_query.iter().filter(|(_, obj)| obj.id.scene == scene_id) {
+            commands
+                .entity(entity)
+                .insert(MiloAttachTo(placer_entity));
+        }
+
+        venue_state.placed_scenes.insert(scene_id);
+        update_parents_events_writer.write(UpdateMiloObjectParents);
+
+        log::info!("Placed \"{}\" on band placer \"{}\"", milo_path, &member.placer);
+    }
+}
+
+fn get_logical_path(path: &str) -> String {
+    // Drop gen folder + platform suffix, resolved when loading
+    let path = path.replace("/gen/", "/");
+
+    match PLATFORM_SUFFIXES.iter().find(|s| path.ends_with(*s)) {
+        Some(suffix) => path[..(path.len() - suffix.len())].to_owned(),
+        None => path,
+    }
+}
+
+fn get_file_stem(path: &str) -> String {
+    path
+        .rsplit('/')
+        .next()
+        .unwrap_or(path)
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+fn get_placer_name(name: &str) -> String {
+    name
+        .to_ascii_lowercase()
+        .trim_end_matches(".bp")
+        .to_owned()
+}
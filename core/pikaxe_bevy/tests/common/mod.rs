@@ -0,0 +1,17 @@
+use bevy::asset::AssetPlugin;
+use bevy::prelude::*;
+use pikaxe_bevy::prelude::*;
+
+/// Headless app with assets used by milo plugin
+pub fn create_headless_app(milo_plugin: MiloPlugin) -> App {
+    let mut app = App::new();
+
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), TransformPlugin));
+    app.init_asset::<Mesh>();
+    app.init_asset::<StandardMaterial>();
+    app.init_asset::<Image>();
+
+    app.add_plugins(milo_plugin);
+
+    app
+}
@@ -0,0 +1,395 @@
+mod common;
+
+use bevy::prelude::*;
+use common::*;
+use pikaxe::{Platform, SystemInfo};
+use pikaxe::io::IOEndian;
+use pikaxe::scene::{BandPlacer, Color3, MatObject, MeshObject, MiloObject as MObject, Object, ObjectDir, ObjectDirBase, TransObject, Vector4, Vert};
+use pikaxe_bevy::prelude::*;
+
+const SCENE_PATH: &str = "test/scene.milo";
+
+fn get_system_info() -> SystemInfo {
+    SystemInfo {
+        version: 25,
+        platform: Platform::X360,
+        endian: IOEndian::Big,
+    }
+}
+
+fn create_trans(name: &str, parent: &str) -> Object {
+    Object::Trans(TransObject {
+        name: name.to_owned(),
+        parent: parent.to_owned(),
+        ..Default::default()
+    })
+}
+
+fn create_mesh(name: &str, parent: &str, mat: &str) -> Object {
+    let vertices = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]]
+        .into_iter()
+        .map(|[x, y, z]| Vert {
+            pos: Vector4 { x, y, z, w: 1.0 },
+            ..Default::default()
+        })
+        .collect();
+
+    Object::Mesh(MeshObject {
+        name: name.to_owned(),
+        parent: parent.to_owned(),
+        mat: mat.to_owned(),
+        vertices,
+        faces: vec![[0, 1, 2]],
+        ..Default::default()
+    })
+}
+
+fn create_mat(name: &str, r: f32, g: f32, b: f32) -> Object {
+    Object::Mat(MatObject {
+        name: name.to_owned(),
+        color: Color3 { r, g, b },
+        alpha: 1.0,
+        ..Default::default()
+    })
+}
+
+fn create_dir(name: &str, entries: Vec<Object>) -> ObjectDir {
+    let mut dir = ObjectDirBase::new();
+    dir.name = name.to_owned();
+    dir.entries = entries;
+
+    ObjectDir::ObjectDir(dir)
+}
+
+fn create_venue_dir() -> ObjectDir {
+    let mut dir = ObjectDirBase::new();
+    dir.name = String::from("test");
+    dir.entries = vec![create_trans("venue.trn", "")];
+    dir.sub_dirs = vec![create_dir("test_geom", Vec::new())];
+
+    ObjectDir::ObjectDir(dir)
+}
+
+fn create_scene() -> (SystemInfo, ObjectDir) {
+    (get_system_info(), create_dir("scene", vec![
+        create_trans("root.trn", ""),
+        create_trans("child.trn", "root.trn"),
+        create_mesh("a.mesh", "child.trn", "red.mat"),
+        create_mesh("b.mesh", "child.trn", "red.mat"),
+        create_mesh("c.mesh", "root.trn", "blue.mat"),
+        create_mesh("orphan.mesh", "missing.trn", "missing.mat"),
+        create_mesh("d.mesh", "root.trn", "red_copy.mat"),
+        create_mat("red.mat", 1.0, 0.0, 0.0),
+        create_mat("red_copy.mat", 1.0, 0.0, 0.0),
+        create_mat("blue.mat", 0.0, 0.0, 1.0),
+    ]))
+}
+
+fn create_app() -> App {
+    // No ark, scenes come from memory
+    let mut app = create_headless_app(MiloPlugin::default());
+
+    app.world_mut()
+        .resource_mut::<MiloState>()
+        .add_source(MiloSource::from_memory([
+            (SCENE_PATH.to_owned(), Box::new(create_scene) as MiloDirFactory),
+        ]));
+
+    app
+}
+
+fn load_scene(app: &mut App, path: &str) {
+    app.world_mut().send_event(LoadMiloScene(path.to_owned()));
+    app.update();
+    app.update();
+}
+
+fn find_object(app: &mut App, name: &str) -> Entity {
+    let mut query = app.world_mut().query::<(Entity, &MiloObject)>();
+
+    query
+        .iter(app.world())
+        .find(|(_, obj)| obj.name.eq(name))
+        .map(|(en, _)| en)
+        .unwrap_or_else(|| panic!("Can't find \"{name}\""))
+}
+
+fn get_parent(app: &App, entity: Entity) -> Option<Entity> {
+    app.world()
+        .get::<ChildOf>(entity)
+        .map(|c| c.parent())
+}
+
+fn get_root(app: &mut App) -> Entity {
+    app.world_mut()
+        .query_filtered::<Entity, With<MiloRoot>>()
+        .single(app.world())
+        .unwrap()
+}
+
+#[test]
+fn load_scene_builds_hierarchy() {
+    let mut app = create_app();
+    load_scene(&mut app, SCENE_PATH);
+
+    let root = get_root(&mut app);
+    let root_trn = find_object(&mut app, "root.trn");
+    let child_trn = find_object(&mut app, "child.trn");
+
+    let a_mesh = find_object(&mut app, "a.mesh");
+    let c_mesh = find_object(&mut app, "c.mesh");
+    let orphan_mesh = find_object(&mut app, "orphan.mesh");
+
+    assert_eq!(get_parent(&app, root_trn), Some(root));
+    assert_eq!(get_parent(&app, child_trn), Some(root_trn));
+    assert_eq!(get_parent(&app, a_mesh), Some(child_trn));
+    assert_eq!(get_parent(&app, c_mesh), Some(root_trn));
+
+    // Missing parent falls back to root
+    assert_eq!(get_parent(&app, orphan_mesh), Some(root));
+}
+
+#[test]
+fn load_scene_adds_components() {
+    let mut app = create_app();
+    load_scene(&mut app, SCENE_PATH);
+
+    let mesh = find_object(&mut app, "a.mesh");
+    let world = app.world();
+
+    let milo_mesh = world.get::<MiloMesh>(mesh).unwrap();
+    assert_eq!(milo_mesh.verts, 3);
+    assert_eq!(milo_mesh.faces, 1);
+
+    let props = world.get::<MiloMeshProps>(mesh).unwrap();
+    assert_eq!(props.mat, "red.mat");
+
+    let trans_props = world.get::<MiloTransProps>(mesh).unwrap();
+    assert_eq!(trans_props.parent, "child.trn");
+
+    assert!(world.get::<Mesh3d>(mesh).is_some());
+    assert!(world.get::<MiloPickable>(mesh).is_some());
+
+    let obj = world.get::<MiloObject>(mesh).unwrap();
+    assert_eq!(obj.dir, "scene");
+
+    let state = world.resource::<MiloState>();
+    let milo_obj = state.get_object(obj.id).unwrap();
+    assert_eq!(milo_obj.get_name(), "a.mesh");
+}
+
+#[test]
+fn load_scene_shares_assets() {
+    let mut app = create_app();
+    load_scene(&mut app, SCENE_PATH);
+
+    let a = find_object(&mut app, "a.mesh");
+    let b = find_object(&mut app, "b.mesh");
+    let c = find_object(&mut app, "c.mesh");
+    let d = find_object(&mut app, "d.mesh");
+    let red_mat = find_object(&mut app, "red.mat");
+
+    let world = app.world();
+    let get_mesh = |en| world.get::<Mesh3d>(en).unwrap().0.clone();
+    let get_mat = |en| world.get::<MeshMaterial3d<StandardMaterial>>(en).unwrap().0.clone();
+
+    // Same geometry
+    assert_eq!(get_mesh(a), get_mesh(b));
+    assert_eq!(get_mesh(a), get_mesh(c));
+
+    // Same mat + identical mats are shared, different mats aren't merged
+    assert_eq!(get_mat(a), get_mat(b));
+    assert_eq!(get_mat(a), get_mat(d));
+    assert_ne!(get_mat(a), get_mat(c));
+    assert_eq!(world.get::<MiloMaterial>(red_mat).unwrap().0, get_mat(a));
+
+    let materials = world.resource::<Assets<StandardMaterial>>();
+    let red = materials.get(&get_mat(a)).unwrap();
+    let blue = materials.get(&get_mat(c)).unwrap();
+
+    assert_eq!(red.base_color, Color::srgba(1.0, 0.0, 0.0, 1.0));
+    assert_eq!(blue.base_color, Color::srgba(0.0, 0.0, 1.0, 1.0));
+}
+
+#[test]
+fn mat_props_update_material() {
+    let mut app = create_app();
+    load_scene(&mut app, SCENE_PATH);
+
+    let mesh = find_object(&mut app, "a.mesh");
+    let red_mat = find_object(&mut app, "red.mat");
+
+    let green = Color::srgba(0.0, 1.0, 0.0, 1.0);
+    app.world_mut().get_mut::<MiloMatProps>(red_mat).unwrap().color = green;
+    app.update();
+
+    let world = app.world();
+    let handle = &world.get::<MeshMaterial3d<StandardMaterial>>(mesh).unwrap().0;
+    let mat = world.resource::<Assets<StandardMaterial>>().get(handle).unwrap();
+
+    assert_eq!(mat.base_color, green);
+}
+
+#[test]
+fn mat_props_detach_shared_material() {
+    let mut app = create_app();
+    load_scene(&mut app, SCENE_PATH);
+
+    let a = find_object(&mut app, "a.mesh");
+    let d = find_object(&mut app, "d.mesh");
+    let red_copy_mat = find_object(&mut app, "red_copy.mat");
+
+    let green = Color::srgba(0.0, 1.0, 0.0, 1.0);
+    app.world_mut().get_mut::<MiloMatProps>(red_copy_mat).unwrap().color = green;
+    app.update();
+
+    let world = app.world();
+    let get_mat = |en| world.get::<MeshMaterial3d<StandardMaterial>>(en).unwrap().0.clone();
+
+    // Edit doesn't leak into identical mat
+    assert_ne!(get_mat(a), get_mat(d));
+    assert_eq!(world.get::<MiloMaterial>(red_copy_mat).unwrap().0, get_mat(d));
+
+    let materials = world.resource::<Assets<StandardMaterial>>();
+    assert_eq!(materials.get(&get_mat(a)).unwrap().base_color, Color::srgba(1.0, 0.0, 0.0, 1.0));
+    assert_eq!(materials.get(&get_mat(d)).unwrap().base_color, green);
+}
+
+#[test]
+fn clear_scene_removes_objects() {
+    let mut app = create_app();
+    load_scene(&mut app, SCENE_PATH);
+
+    let mesh = find_object(&mut app, "a.mesh");
+    let old_id = app.world().get::<MiloObject>(mesh).unwrap().id;
+
+    app.world_mut().send_event(ClearMiloScene);
+    app.update();
+
+    let mut query = app.world_mut().query::<&MiloObject>();
+    assert_eq!(query.iter(app.world()).count(), 0);
+
+    let state = app.world().resource::<MiloState>();
+    assert_eq!(state.get_object_count(), 0);
+    assert!(state.get_object(old_id).is_none());
+
+    // Shared handles are released
+    let asset_cache = app.world().resource::<MiloAssetCache>();
+    assert!(asset_cache.meshes.is_empty());
+    assert!(asset_cache.materials.is_empty());
+
+    // Root is kept
+    get_root(&mut app);
+}
+
+#[test]
+fn remove_object_keeps_other_ids() {
+    let mut app = create_app();
+    load_scene(&mut app, SCENE_PATH);
+
+    let a = find_object(&mut app, "a.mesh");
+    let b = find_object(&mut app, "b.mesh");
+
+    let a_id = app.world().get::<MiloObject>(a).unwrap().id;
+    let b_id = app.world().get::<MiloObject>(b).unwrap().id;
+
+    let mut state = app.world_mut().resource_mut::<MiloState>();
+
+    let removed = state.remove_object(a_id).unwrap();
+    assert_eq!(removed.get_name(), "a.mesh");
+
+    assert!(state.get_object(a_id).is_none());
+    assert_eq!(state.get_object(b_id).unwrap().get_name(), "b.mesh");
+
+    // Re-used slot doesn't revive old id
+    let new_id = state.insert_object(a_id.scene, create_trans("new.trn", "")).unwrap();
+
+    assert!(state.get_object(a_id).is_none());
+    assert_eq!(state.get_object(new_id).unwrap().get_name(), "new.trn");
+}
+
+#[test]
+fn load_missing_scene_is_skipped() {
+    let mut app = create_app();
+    load_scene(&mut app, "test/missing.milo");
+
+    let mut query = app.world_mut().query::<&MiloObject>();
+    assert_eq!(query.iter(app.world()).count(), 0);
+}
+
+#[test]
+fn missing_ark_is_skipped() {
+    let mut app = create_headless_app(MiloPlugin {
+        ark_path: Some("missing/main.hdr".into()),
+        ..Default::default()
+    });
+
+    app.update();
+
+    assert!(app.world().resource::<MiloState>().sources.is_empty());
+}
+
+#[test]
+fn path_index_resolves_paths() {
+    let index = MiloPathIndex::new(vec![
+        String::from("char/main/gen/main.milo_xbox"),
+        String::from("songs/Song/song.mid"),
+        String::from("ui/a.milo_xbox"),
+        String::from("ui/a.milo_ps3"),
+    ]);
+
+    let resolve = |p| index.resolve(p).map(|i| index.get_path(i));
+
+    assert_eq!(resolve("songs/Song/song.mid").unwrap(), "songs/Song/song.mid");
+    assert_eq!(resolve("./songs/song/SONG.mid").unwrap(), "songs/Song/song.mid");
+    assert_eq!(resolve("char/main/main.milo").unwrap(), "char/main/gen/main.milo_xbox");
+
+    assert!(matches!(resolve("ui/a.milo"), Err(MiloPathError::Ambiguous { .. })));
+    assert!(matches!(resolve("ui/b.milo"), Err(MiloPathError::NotFound(_))));
+}
+
+#[test]
+fn load_venue_places_band() {
+    let mut app = create_app();
+
+    app.world_mut()
+        .resource_mut::<MiloState>()
+        .add_source(MiloSource::from_memory([
+            (String::from("world/test/test.milo"), Box::new(|| (get_system_info(), create_venue_dir())) as MiloDirFactory),
+            (String::from("world/test/unused.milo"), Box::new(|| (get_system_info(), create_dir("unused", Vec::new()))) as MiloDirFactory),
+            (String::from("world/test/og/gen/test_geom.milo_xbox"), Box::new(|| (get_system_info(), create_dir("test_geom", vec![
+                Object::BandPlacer(BandPlacer {
+                    name: String::from("guitar.bp"),
+                    ..Default::default()
+                }),
+            ]))) as MiloDirFactory),
+            (String::from("char/test/test.milo"), Box::new(|| (get_system_info(), create_dir("test_char", vec![
+                create_trans("bone_pelvis.trn", ""),
+            ]))) as MiloDirFactory),
+        ]));
+
+    app.world_mut()
+        .resource_mut::<MiloVenueBand>()
+        .members
+        .push(MiloBandMember {
+            placer: String::from("guitar"),
+            milo_path: String::from("char/test/test.milo"),
+        });
+
+    let state = app.world().resource::<MiloState>();
+    assert_eq!(find_venue_milos(state, "test"), vec![
+        String::from("world/test/og/test_geom.milo"),
+        String::from("world/test/test.milo"),
+    ]);
+
+    app.world_mut().send_event(LoadVenue(String::from("test")));
+
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let placer = find_object(&mut app, "guitar.bp");
+    let pelvis = find_object(&mut app, "bone_pelvis.trn");
+
+    assert_eq!(get_parent(&app, pelvis), Some(placer));
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use common::*;
+use pikaxe_bevy::prelude::*;
+use std::time::Duration;
+
+// 2 tracks, 480 ticks per beat
+// Tempo: 120 bpm, 240 bpm at tick 1920
+// "TEST": note 60 at tick 0 (480 long), "[idle]" text at tick 480
+const SONG_MIDI: &[u8] = &[
+    b'M', b'T', b'h', b'd', 0x00, 0x00, 0x00, 0x06, 0x00, 0x01, 0x00, 0x02, 0x01, 0xE0,
+    b'M', b'T', b'r', b'k', 0x00, 0x00, 0x00, 19,
+    0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20,
+    0x8F, 0x00, 0xFF, 0x51, 0x03, 0x03, 0xD0, 0x90,
+    0x00, 0xFF, 0x2F, 0x00,
+    b'M', b'T', b'r', b'k', 0x00, 0x00, 0x00, 31,
+    0x00, 0xFF, 0x03, 0x04, b'T', b'E', b'S', b'T',
+    0x00, 0x90, 0x3C, 0x64,
+    0x83, 0x60, 0x80, 0x3C, 0x00,
+    0x00, 0xFF, 0x01, 0x06, b'[', b'i', b'd', b'l', b'e', b']',
+    0x00, 0xFF, 0x2F, 0x00,
+];
+
+// 1 track, 480 ticks per beat
+// Time sig: 4/4, 3/4 at tick 1920
+const SONG_MIDI_3_4: &[u8] = &[
+    b'M', b'T', b'h', b'd', 0x00, 0x00, 0x00, 0x06, 0x00, 0x00, 0x00, 0x01, 0x01, 0xE0,
+    b'M', b'T', b'r', b'k', 0x00, 0x00, 0x00, 21,
+    0x00, 0xFF, 0x58, 0x04, 0x04, 0x02, 0x18, 0x08,
+    0x8F, 0x00, 0xFF, 0x58, 0x04, 0x03, 0x02, 0x18, 0x08,
+    0x00, 0xFF, 0x2F, 0x00,
+];
+
+fn load_song() -> MiloSong {
+    MiloSong::from_bytes("songs/test/test.mid", SONG_MIDI).unwrap()
+}
+
+#[test]
+fn song_reads_tracks() {
+    let song = load_song();
+
+    assert_eq!(song.tempo_map.ticks_per_beat, 480);
+    assert_eq!(song.length, 1920);
+
+    let track = song.get_track("TEST").unwrap();
+
+    assert_eq!(track.notes.len(), 1);
+    assert_eq!(track.notes[0].tick, 0);
+    assert_eq!(track.notes[0].length, 480);
+    assert_eq!(track.notes[0].pitch, 60);
+    assert_eq!(track.notes[0].velocity, 100);
+
+    assert_eq!(track.texts.len(), 1);
+    assert_eq!(track.texts[0].tick, 480);
+    assert_eq!(track.texts[0].text, "[idle]");
+}
+
+#[test]
+fn tempo_map_converts_ticks() {
+    let tempo_map = load_song().tempo_map;
+
+    assert_eq!(tempo_map.tick_to_seconds(480), 0.5);
+    assert_eq!(tempo_map.tick_to_seconds(1920), 2.0);
+    assert_eq!(tempo_map.tick_to_seconds(2400), 2.25);
+
+    assert_eq!(tempo_map.seconds_to_tick(1.0), 960);
+    assert_eq!(tempo_map.seconds_to_tick(2.25), 2400);
+
+    assert_eq!(tempo_map.tick_to_beat(1920), 4.0);
+    assert_eq!(tempo_map.tick_to_measure(1920), 1.0);
+    assert_eq!(tempo_map.get_tempo(2000).mpq, 250_000);
+}
+
+#[test]
+fn tempo_map_reads_time_sigs() {
+    let tempo_map = MiloSong::from_bytes("songs/test/waltz.mid", SONG_MIDI_3_4).unwrap().tempo_map;
+
+    assert_eq!(tempo_map.get_time_sig(0).numerator, 4);
+    assert_eq!(tempo_map.get_time_sig(1920).numerator, 3);
+    assert_eq!(tempo_map.get_time_sig(1920).denominator, 4);
+
+    assert_eq!(tempo_map.tick_to_measure(1920), 1.0);
+    assert_eq!(tempo_map.tick_to_measure(2640), 1.5);
+    assert_eq!(tempo_map.tick_to_measure(3360), 2.0);
+}
+
+#[test]
+fn song_time_fires_events() {
+    let mut app = create_headless_app(MiloPlugin::default());
+
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)));
+    app.insert_resource(load_song());
+    app.world_mut().resource_mut::<MiloSongTime>().playing = true;
+
+    let mut notes = Vec::new();
+    let mut texts = Vec::new();
+
+    for _ in 0..10 {
+        app.update();
+
+        let world = app.world_mut();
+        notes.extend(world.resource_mut::<Events<MiloSongNoteEvent>>().drain());
+        texts.extend(world.resource_mut::<Events<MiloSongTextEvent>>().drain());
+    }
+
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].track, "TEST");
+    assert_eq!(notes[0].length_seconds, 0.5);
+
+    assert_eq!(texts.len(), 1);
+    assert_eq!(texts[0].text, "[idle]");
+
+    let song_time = app.world().resource::<MiloSongTime>();
+    assert!(song_time.tick >= 480);
+    assert!(song_time.playing);
+}